@@ -0,0 +1,89 @@
+//! Baseline timings for `Overlay` hot paths. Run with `cargo bench`.
+//!
+//! Plain `Instant` timing (criterion isn't a dependency): each case reports the mean time per
+//! iteration over a fixed number of iterations, after a warm-up pass.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use grug_soa::Overlay;
+
+const INSTANCES: usize = 10_000;
+const ITERS: u32 = 100_000;
+
+fn bench(name: &str, iters: u32, mut f: impl FnMut()) {
+    for _ in 0..iters / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..iters {
+        f();
+    }
+    let per_iter: Duration = start.elapsed() / iters;
+    println!("{name:<40} {per_iter:>12.2?}/iter");
+}
+
+/// Overlay with `INSTANCES` instances of prototype 0; every even instance is overridden.
+fn overlay() -> Overlay<String> {
+    let mut overlay = Overlay::default();
+    overlay.dense_data.push("prototype value".to_string());
+    for i in 0..INSTANCES {
+        overlay.push_instance();
+        if i % 2 == 0 {
+            overlay.set(i, format!("override {i}"));
+        }
+    }
+    overlay
+}
+
+fn main() {
+    let overlay = overlay();
+    let proto_ids = vec![0; INSTANCES];
+
+    bench("get (prototype fallback)", ITERS, || {
+        black_box(overlay.get(black_box(1), 0));
+    });
+
+    bench("get (override)", ITERS, || {
+        black_box(overlay.get(black_box(2), 0));
+    });
+
+    let mut cow = overlay.clone();
+    bench("get_mut (copy-on-write)", ITERS, || {
+        cow.clear_override(1);
+        black_box(cow.get_mut(black_box(1), 0));
+    });
+
+    let mut set = overlay.clone();
+    bench("set (existing override)", ITERS, || {
+        set.set(black_box(2), "new value".to_string());
+    });
+
+    bench("clone + drain 10k via swap_remove_instance", 100, || {
+        let mut overlay = overlay.clone();
+        for i in (0..INSTANCES).rev() {
+            overlay.swap_remove_instance(black_box(i / 2));
+        }
+        black_box(overlay);
+    });
+
+    bench("to_dense_vec (10k instances)", 100, || {
+        black_box(overlay.to_dense_vec(&proto_ids));
+    });
+
+    // Memory rather than time: HashMap capacity after clearing 90% of the overrides.
+    let mut sparse = overlay.clone();
+    for i in (0..INSTANCES).filter(|i| i % 20 != 0) {
+        sparse.clear_override(i);
+    }
+    let before = sparse.sparse_data.capacity();
+    sparse.defrag_sparse_data();
+    let after = sparse.sparse_data.capacity();
+    let bucket = std::mem::size_of::<(usize, String)>();
+    println!(
+        "{:<40} {before:>6} -> {after} slots (~{} -> {} KiB)",
+        "defrag_sparse_data capacity",
+        before * bucket / 1024,
+        after * bucket / 1024
+    );
+}
@@ -2,8 +2,113 @@
 macro_rules! impl_load_prototype {
     // Preferred form: requires `prototype_id: usize` so prototype IDs are auto-assigned on load
     // and copied into runtime instances on spawn.
-    ($ecs:ty { prototype_id: usize, $($field:ident : $ty:ty),* $(,)? }) => {
+    //
+    // The `prototype_id` column can also hold a typed id, e.g. a `#[repr(usize)]` prototype enum,
+    // as long as it is `Copy + Into<usize> + From<usize>`. Generated methods still take and return
+    // prototype ids as `usize`, converting when they read or write the column.
+    //
+    // A field can be followed by `[index_by]` to maintain a `name_index: HashMap<String, usize>`
+    // field on the struct (mapping that field's prototype value to its prototype_id) and generate
+    // `prototype_by_name`. Only one field per table can be indexed. Names needn't be unique (the
+    // default prototypes `load_prototype_with_id` fills gaps with all share one): a name maps to
+    // the prototype most recently loaded with it, and when that prototype is overwritten under
+    // another name, to the highest remaining prototype_id that has it.
+    //
+    // The invocation can be prefixed with `#[soa(deny_unknown_fields)]`, which makes
+    // `load_prototype` (and `load_prototype_batch`) return `Err(UnknownFieldError)` for JSON keys
+    // that aren't registered fields. The default (`allow_unknown_fields`) silently ignores them.
+    //
+    // Optionally follow the fields with `unregistered { a, b }` listing the struct's fields that
+    // are deliberately not registered. The macro then checks at compile time that every struct
+    // field is either registered or listed, so forgetting to register a field is a compile error
+    // instead of a misaligned table.
+    //
+    // Generated methods bound field types with `for<'h> $ty: Bound` rather than `$ty: Bound`.
+    // A bound on a concrete type that doesn't hold is a compile error (rust-lang/rust#48214),
+    // so a plain bound would make e.g. a non-`Clone` field break the whole impl; the
+    // higher-ranked form only makes the methods that need the bound unavailable.
+    (#[soa($mode:ident)] $($rest:tt)*) => {
+        $crate::impl_load_prototype!(@impl $mode $($rest)*);
+    };
+
+    (@impl $mode:ident $ecs:ty { prototype_id: $pid:ty, $($field:ident : $ty:ty $([$attr:ident])?),* $(,)? } $(unregistered $unregistered:tt)?) => {
         impl $ecs {
+            /// Entity `idx`'s `prototype_id` as a row index.
+            fn prototype_index(&self, idx: usize) -> usize {
+                <$pid as ::core::convert::Into<usize>>::into(self.prototype_id[idx])
+            }
+
+            $crate::__impl_field_check!(($($field),*) $($unregistered)?);
+
+            $($(
+                $crate::__impl_load_prototype_attr!(@methods $attr $field : $ty);
+            )?)*
+
+            $crate::__impl_load_prototype_mode!($mode);
+
+            $crate::__impl_json_schema!($($field : $ty),*);
+
+            /// Names of the registered component fields (not including `prototype_id`).
+            pub fn component_names() -> &'static [&'static str] {
+                &[$(::core::stringify!($field)),*]
+            }
+
+            /// `(field name, type name)` of each registered component field, with type names as
+            /// written in the macro invocation.
+            pub const fn field_names_and_types() -> &'static [(&'static str, &'static str)] {
+                &[$((::core::stringify!($field), ::core::stringify!($ty))),*]
+            }
+
+            /// A blank prototype: every registered field's `Default` value, as a JSON object
+            /// keyed by field name (e.g. for an editor's "new prototype" dialog).
+            pub fn component_default_json() -> ::serde_json::Value
+            where
+                $(
+                    for<'h> $ty: ::core::default::Default + ::serde::Serialize,
+                )*
+            {
+                let mut obj = ::serde_json::Map::new();
+                $(
+                    obj.insert(
+                        ::core::stringify!($field).to_string(),
+                        ::serde_json::to_value(<$ty as ::core::default::Default>::default())
+                            .expect("failed to serialize default field value"),
+                    );
+                )*
+                ::serde_json::Value::Object(obj)
+            }
+
+            /// `size_of` the value type of field `field_name` (for an overlay field, the size of
+            /// one override value), or 0 if it isn't a registered field.
+            pub fn field_size_of(field_name: &str) -> usize {
+                match field_name {
+                    $(
+                        ::core::stringify!($field) => ::core::mem::size_of::<$ty>(),
+                    )*
+                    _ => 0,
+                }
+            }
+
+            /// Whether `field_name` is backed by an overlay (`Overlay<T>` and friends) rather
+            /// than dense storage like `Vec<T>`. `false` for unknown fields.
+            pub fn field_type_is_overlay(field_name: &str) -> bool {
+                match field_name {
+                    $(
+                        ::core::stringify!($field) => {
+                            $crate::__storage_is_overlay::<$ecs, $ty, _>(|soa| &soa.$field)
+                        }
+                    )*
+                    _ => false,
+                }
+            }
+
+            /// Bytes of field data `n_prototypes` prototypes take in a prototypes table, where
+            /// every field holds one value per prototype. Heap data owned by the values isn't
+            /// included.
+            pub fn total_prototype_memory(n_prototypes: usize) -> usize {
+                (0 $(+ ::core::mem::size_of::<$ty>())*) * n_prototypes
+            }
+
             /// Constructs a runtime table from a prototype table.
             ///
             /// This seeds any `Overlay<T>` fields with prototype `dense_data`, so runtime reads
@@ -11,7 +116,7 @@ macro_rules! impl_load_prototype {
             pub fn new_from_prototypes(prototypes: &$ecs) -> Self
             where
                 $(
-                    $ty: ::core::clone::Clone + ::core::default::Default + ::serde::de::DeserializeOwned,
+                    for<'h> $ty: ::core::default::Default + ::serde::de::DeserializeOwned,
                 )*
             {
                 let mut out = <$ecs as ::core::default::Default>::default();
@@ -33,10 +138,12 @@ macro_rules! impl_load_prototype {
             pub fn spawn_entity(&mut self, prototype: &$ecs, prototype_index: usize)
             where
                 $(
-                    // The spawned table may store either a dense Vec<T> or a sparse Overlay<T>.
-                    $ty: ::core::clone::Clone + ::core::default::Default + ::serde::de::DeserializeOwned,
+                    // The spawned table may store a dense Vec<T>, a sparse Overlay<T> or an OverlayNoCopy<T>;
+                    // each storage impl adds its own bounds (e.g. Clone).
+                    for<'h> $ty: ::core::default::Default + ::serde::de::DeserializeOwned,
                 )*
             {
+                debug_assert!(self.len_consistent(), "spawn_entity: field lengths are inconsistent");
                 // Always copy prototype_id to instances (caller can store prototype_id as a normal field).
                 self.prototype_id.push(prototype.prototype_id[prototype_index]);
                 $(
@@ -48,294 +155,5938 @@ macro_rules! impl_load_prototype {
                 )*
             }
 
+            /// Like `spawn_entity`, but only the registered fields named in `fields` are copied
+            /// from the prototype; every other field starts at its `Default` value.
+            ///
+            /// Unknown names in `fields` are ignored.
+            pub fn partial_spawn_entity(&mut self, prototype: &$ecs, proto_idx: usize, fields: &[&str])
+            where
+                $(
+                    for<'h> $ty: ::core::default::Default + ::serde::de::DeserializeOwned,
+                )*
+            {
+                self.prototype_id.push(prototype.prototype_id[proto_idx]);
+                $(
+                    if fields.contains(&stringify!($field)) {
+                        <_ as $crate::Storage<$ty>>::push_from_prototype(
+                            &mut self.$field,
+                            &prototype.$field,
+                            proto_idx,
+                        );
+                    } else {
+                        <_ as $crate::Storage<$ty>>::push_default(&mut self.$field);
+                    }
+                )*
+            }
+
             /// Removes an entity by index using `Vec::swap_remove` for every registered field.
             ///
             /// This is an O(1) removal but does **not** preserve ordering (the last entity is moved
             /// into `index`).
             pub fn swap_remove(&mut self, index: usize) {
+                debug_assert!(self.len_consistent(), "swap_remove: field lengths are inconsistent");
                 self.prototype_id.swap_remove(index);
                 $(
                     <_ as $crate::Storage<$ty>>::swap_remove(&mut self.$field, index);
                 )*
             }
 
-            /// Loads a prototype from a JSON object into the ECS.
-            pub fn load_prototype(&mut self, prototype: ::serde_json::Value) {
-                let obj = prototype
-                    .as_object()
-                    .expect("prototype must be a JSON object");
-                let null = ::serde_json::Value::Null;
+            /// Removes every entity for which `pred(i, self)` is false, returning how many were
+            /// removed.
+            ///
+            /// Entities are visited from last to first and removed with `swap_remove`, so each
+            /// removal only moves an entity that was already visited. `i` is the entity's
+            /// *current* index, which may differ from its index before the call.
+            pub fn retain_entities(&mut self, pred: impl Fn(usize, &Self) -> bool) -> usize {
+                let mut removed = 0;
+                for i in (0..self.len()).rev() {
+                    if !pred(i, self) {
+                        self.swap_remove(i);
+                        removed += 1;
+                    }
+                }
+                removed
+            }
 
+            /// The first entity index for which `pred(i, self)` is true.
+            pub fn find_entity(&self, pred: impl Fn(usize, &Self) -> bool) -> Option<usize> {
+                (0..self.len()).find(|&i| pred(i, self))
+            }
+
+            /// Every entity index for which `pred(i, self)` is true, ascending.
+            pub fn find_entities(&self, pred: impl Fn(usize, &Self) -> bool) -> ::std::vec::Vec<usize> {
+                (0..self.len()).filter(|&i| pred(i, self)).collect()
+            }
+
+            /// Whether `pred(i, self)` is true for any entity.
+            pub fn any_entity(&self, pred: impl Fn(usize, &Self) -> bool) -> bool {
+                (0..self.len()).any(|i| pred(i, self))
+            }
+
+            /// Appends a copy of live entity `src_idx` (same prototype_id, same overrides),
+            /// returning the new entity's index.
+            pub fn clone_entity(&mut self, src_idx: usize) -> usize
+            where
                 $(
-                    if let Some(v) = obj.get(::core::stringify!($field)) {
-                        <_ as $crate::Storage<$ty>>::push_json(&mut self.$field, v);
-                    } else {
-                        // Missing field => default prototype value, regardless of storage backend.
-                        <_ as $crate::Storage<$ty>>::push_json(&mut self.$field, &null);
+                    for<'h> $ty: ::core::clone::Clone,
+                )*
+            {
+                let new_idx = self.prototype_id.len();
+                self.prototype_id.push(self.prototype_id[src_idx]);
+                $(
+                    <_ as $crate::Storage<$ty>>::clone_entity(&mut self.$field, src_idx);
+                )*
+                new_idx
+            }
+
+            /// A new runtime table holding copies of the entities at `indices`, in that order
+            /// (e.g. a streamed-out chunk of the scene). Entity `indices[k]` becomes entity `k`.
+            ///
+            /// The new table keeps this table's copies of the prototypes, so `prototype_id`s
+            /// carry over unchanged. Overlay fields get each entity's effective value as an
+            /// override, so they don't depend on the prototype data staying the same.
+            /// Unregistered fields are left at their defaults.
+            pub fn clone_partial(&self, indices: &[usize]) -> Self
+            where
+                $(
+                    for<'h> $ty: ::core::clone::Clone + ::core::default::Default + ::serde::de::DeserializeOwned,
+                )*
+            {
+                let mut out = <$ecs as ::core::default::Default>::default();
+                $(
+                    <_ as $crate::Storage<$ty>>::init_from_prototypes(&mut out.$field, &self.$field);
+                )*
+                for (new_idx, &idx) in indices.iter().enumerate() {
+                    out.prototype_id.push(self.prototype_id[idx]);
+                    let proto_id = self.prototype_index(idx);
+                    $(
+                        let value = <_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, idx, proto_id).into_owned();
+                        <_ as $crate::Storage<$ty>>::push_default(&mut out.$field);
+                        <_ as $crate::Storage<$ty>>::set_entity(&mut out.$field, new_idx, value);
+                    )*
+                }
+                out
+            }
+
+            /// Re-points a live entity at a different prototype of `prototypes` (e.g. a goblin
+            /// promoted to orc).
+            ///
+            /// Overlay fields without an override implicitly pick up the new prototype's values;
+            /// overrides and dense fields keep their current values. Panics if `new_proto_id`
+            /// isn't a prototype of `prototypes`.
+            pub fn migrate_prototype(&mut self, prototypes: &Self, entity_idx: usize, new_proto_id: usize) {
+                Self::check_migration_target(prototypes, new_proto_id);
+                self.prototype_id[entity_idx] = ::core::convert::From::from(new_proto_id);
+            }
+
+            /// Like `migrate_prototype`, but overlay fields without an override first copy the
+            /// old prototype's value into one, so every field keeps its current value.
+            pub fn migrate_prototype_keep_values(&mut self, prototypes: &Self, entity_idx: usize, new_proto_id: usize) {
+                Self::check_migration_target(prototypes, new_proto_id);
+                let old_proto_id = self.prototype_index(entity_idx);
+                $(
+                    <_ as $crate::Storage<$ty>>::pin_entity(&mut self.$field, entity_idx, old_proto_id);
+                )*
+                self.prototype_id[entity_idx] = ::core::convert::From::from(new_proto_id);
+            }
+
+            fn check_migration_target(prototypes: &Self, new_proto_id: usize) {
+                assert!(
+                    new_proto_id < prototypes.prototype_count(),
+                    "migrate_prototype: prototype {new_proto_id} out of range ({} prototypes)",
+                    prototypes.prototype_count()
+                );
+            }
+
+            /// Returns true if entities `a` and `b` have equal effective values in every field.
+            ///
+            /// Overlay fields are compared through their prototype fallback.
+            pub fn entity_equals(&self, a: usize, b: usize) -> bool
+            where
+                $(
+                    for<'h> $ty: ::core::cmp::PartialEq + ::core::clone::Clone,
+                )*
+            {
+                let (proto_a, proto_b) = (self.prototype_index(a), self.prototype_index(b));
+                $(
+                    if <_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, a, proto_a)
+                        != <_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, b, proto_b)
+                    {
+                        return false;
                     }
                 )*
+                true
+            }
 
-                // Auto-assign prototype_id if the JSON didn't include it (or if it did; we ignore it).
-                let next_id = self.prototype_id.len();
-                self.prototype_id.push(next_id);
+            /// Returns true if entity `entity_idx` still matches prototype `proto_idx` of the
+            /// `prototypes` table in every field.
+            pub fn entities_equal_to_prototype(
+                &self,
+                entity_idx: usize,
+                prototypes: &$ecs,
+                proto_idx: usize,
+            ) -> bool
+            where
+                $(
+                    for<'h> $ty: ::core::cmp::PartialEq + ::core::clone::Clone,
+                )*
+            {
+                let entity_proto = self.prototype_index(entity_idx);
+                $(
+                    if *<_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, entity_idx, entity_proto)
+                        != *<_ as $crate::Storage<$ty>>::prototype(&prototypes.$field, proto_idx)
+                    {
+                        return false;
+                    }
+                )*
+                true
             }
-        }
-    };
 
-    // Explicit error for old macro call sites that don't declare prototype_id.
-    ($ecs:ty { $($field:ident : $ty:ty),* $(,)? }) => {
-        compile_error!(
-            "impl_load_prototype!(...) now requires `prototype_id: usize` as the first field in the macro invocation, so prototype IDs can be auto-assigned and copied on spawn."
-        );
-    };
-}
+            /// Resets every live entity of prototype `proto_id` back to the prototype's values
+            /// by clearing their overrides in all overlay fields. Returns the number cleared.
+            pub fn reset_all_entities_of_prototype(&mut self, proto_id: usize) -> usize {
+                let proto_ids = $crate::__prototype_ids_as_usize(&self.prototype_id);
+                0 $(+ <_ as $crate::Storage<$ty>>::clear_overrides_for_prototype(
+                    &mut self.$field,
+                    proto_id,
+                    &proto_ids,
+                ))*
+            }
 
-use std::collections::HashMap;
-use serde::de::DeserializeOwned;
-use serde_json::Value;
+            /// Total number of per-instance overrides across all overlay fields.
+            pub fn total_override_count(&self) -> usize {
+                0 $(+ <_ as $crate::Storage<$ty>>::override_count(&self.$field))*
+            }
 
-/// The secret sauce: A trait that masks the difference between Vec and Overlay
-pub trait Storage<T> {
-    /// Initialize a runtime table field from the prototypes table.
-    ///
-    /// - For dense `Vec<T>`: no-op (runtime instances start empty).
-    /// - For `Overlay<T>`: clones prototype `dense_data` so runtime lookups can fall back.
-    fn init_from_prototypes(&mut self, prototypes: &Self);
-    fn push_json(&mut self, json: &Value);
-    fn push_from_prototype(&mut self, source: &Self, proto_idx: usize);
-    fn swap_remove(&mut self, index: usize);
-}
+            /// `(field_name, capacity, len)` for `prototype_id` and every registered field, to
+            /// spot over- or under-allocated fields. Dense fields report `Vec::capacity`/`len`;
+            /// overlay fields report their prototype data's capacity and their instance count.
+            pub fn capacity_summary(&self) -> ::std::vec::Vec<(&'static str, usize, usize)> {
+                ::std::vec![
+                    ("prototype_id", self.prototype_id.capacity(), self.prototype_id.len()),
+                    $((
+                        ::core::stringify!($field),
+                        <_ as $crate::Storage<$ty>>::row_capacity(&self.$field),
+                        <_ as $crate::Storage<$ty>>::entity_count(&self.$field).unwrap_or(0),
+                    )),*
+                ]
+            }
 
-// Implement for standard Vec (Dense storage)
-impl<T> Storage<T> for Vec<T> 
-where T: Clone + Default + DeserializeOwned 
-{
-    fn init_from_prototypes(&mut self, _prototypes: &Self) {
-        // Runtime instance storage starts empty for dense Vec.
+            /// Approximate bytes entity `entity_idx` costs: `size_of::<T>()` for every dense
+            /// field, plus overlay fields where it has an override. Overlay fields without one
+            /// share the prototype's value, so they're counted as free. Heap data owned by the
+            /// values themselves (e.g. `String` contents) isn't included.
+            pub fn entity_memory_size(&self, entity_idx: usize) -> usize {
+                0 $(+ match <_ as $crate::Storage<$ty>>::override_state(&self.$field, entity_idx) {
+                    ::core::option::Option::Some(false) => 0,
+                    _ => ::core::mem::size_of::<$ty>(),
+                })*
+            }
+
+            /// Swaps field `field_name` between entities `a` and `b` (e.g. trading an item),
+            /// leaving their other fields alone.
+            ///
+            /// Overlay fields swap effective values, so an entity whose new value comes from a
+            /// different prototype gains an override (see `Storage::swap_entities`).
+            pub fn swap_field(
+                &mut self,
+                a: usize,
+                b: usize,
+                field_name: &str,
+            ) -> ::core::result::Result<(), $crate::UnknownFieldError>
+            where
+                $(
+                    for<'h> $ty: ::core::clone::Clone,
+                )*
+            {
+                match field_name {
+                    $(
+                        ::core::stringify!($field) => {
+                            let (proto_a, proto_b) = (self.prototype_index(a), self.prototype_index(b));
+                            <_ as $crate::Storage<$ty>>::swap_entities(&mut self.$field, a, proto_a, b, proto_b);
+                        }
+                    )*
+                    _ => {
+                        return ::core::result::Result::Err($crate::UnknownFieldError {
+                            field: field_name.to_string(),
+                        });
+                    }
+                }
+                ::core::result::Result::Ok(())
+            }
+
+            /// Number of overrides in field `field_name` (0 for dense fields and unknown names).
+            pub fn override_count_for_field(&self, field_name: &str) -> usize {
+                match field_name {
+                    $(
+                        ::core::stringify!($field) => {
+                            <_ as $crate::Storage<$ty>>::override_count(&self.$field)
+                        }
+                    )*
+                    _ => 0,
+                }
+            }
+
+            /// Counts entities whose effective `field_name` value, as JSON, satisfies `pred`.
+            ///
+            /// Slow (serializes every value), but handy for scripted queries from a console.
+            /// Returns 0 if `field_name` isn't a registered field.
+            pub fn count_entities_with(
+                &self,
+                field_name: &str,
+                pred: impl Fn(&::serde_json::Value) -> bool,
+            ) -> usize
+            where
+                $(
+                    for<'h> $ty: ::serde::Serialize + ::core::clone::Clone,
+                )*
+            {
+                match field_name {
+                    $(
+                        ::core::stringify!($field) => (0..self.prototype_id.len())
+                            .filter(|&i| {
+                                let value = <_ as $crate::Storage<$ty>>::get_entity_cow(
+                                    &self.$field,
+                                    i,
+                                    self.prototype_index(i),
+                                );
+                                pred(&::serde_json::to_value(&*value).expect("failed to serialize field"))
+                            })
+                            .count(),
+                    )*
+                    _ => 0,
+                }
+            }
+
+            /// `f` applied to every entity's effective `field_name` value as JSON, in entity order,
+            /// for tooling that doesn't know the field types (e.g. validation scripts).
+            /// Empty if `field_name` isn't a registered field.
+            pub fn map_field_values(
+                &self,
+                field_name: &str,
+                f: impl Fn(&::serde_json::Value) -> ::serde_json::Value,
+            ) -> ::std::vec::Vec<::serde_json::Value>
+            where
+                $(
+                    for<'h> $ty: ::serde::Serialize,
+                )*
+            {
+                match field_name {
+                    $(
+                        ::core::stringify!($field) => (0..self.prototype_id.len())
+                            .map(|i| {
+                                f(&<_ as $crate::Storage<$ty>>::get_as_json(&self.$field, i, self.prototype_index(i)))
+                            })
+                            .collect(),
+                    )*
+                    _ => ::std::vec::Vec::new(),
+                }
+            }
+
+            /// Hash of entity `entity_idx`'s `prototype_id` and effective field values, for cheap
+            /// change detection: keep last frame's checksums in a `Vec<u64>` and compare.
+            ///
+            /// Uses `DefaultHasher`, so checksums are only comparable within one build of the
+            /// program; don't persist them.
+            pub fn entity_checksum(&self, entity_idx: usize) -> u64
+            where
+                $(
+                    for<'h> $ty: ::core::hash::Hash + ::core::clone::Clone,
+                )*
+            {
+                use ::core::hash::{Hash, Hasher};
+
+                let proto_id = self.prototype_index(entity_idx);
+                let mut hasher = ::std::hash::DefaultHasher::new();
+                proto_id.hash(&mut hasher);
+                $(
+                    <_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, entity_idx, proto_id).hash(&mut hasher);
+                )*
+                hasher.finish()
+            }
+
+            /// Counts entities whose effective `field_name` value equals `target` (deserialized as
+            /// the field's type), e.g. for analytics. Returns 0 for unknown fields, or if `target`
+            /// doesn't deserialize as the field's type.
+            pub fn instance_count_matching(&self, field_name: &str, target: &::serde_json::Value) -> usize
+            where
+                $(
+                    for<'h> $ty: ::core::cmp::PartialEq + ::serde::de::DeserializeOwned + ::core::clone::Clone,
+                )*
+            {
+                match field_name {
+                    $(
+                        ::core::stringify!($field) => {
+                            let ::core::result::Result::Ok(target) = <$ty as ::serde::Deserialize>::deserialize(target) else {
+                                return 0;
+                            };
+                            (0..self.prototype_id.len())
+                                .filter(|&i| {
+                                    *<_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, i, self.prototype_index(i)) == target
+                                })
+                                .count()
+                        }
+                    )*
+                    _ => 0,
+                }
+            }
+
+            /// Typed version of `count_entities_with`: counts entities whose effective value of
+            /// the field chosen by `field` (e.g. `|soa| &soa.hp`) satisfies `pred`.
+            pub fn count_entities_where<T: ::core::clone::Clone, S: $crate::Storage<T>>(
+                &self,
+                field: impl Fn(&Self) -> &S,
+                pred: impl Fn(&T) -> bool,
+            ) -> usize {
+                let storage = field(self);
+                (0..self.prototype_id.len())
+                    .filter(|&i| pred(&storage.get_entity_cow(i, self.prototype_index(i))))
+                    .count()
+            }
+
+            /// Iterates the effective value of every entity for the field chosen by `field`
+            /// (e.g. `soa.iter_field(|soa| &soa.hp)`), resolving overlay fields through this
+            /// table's `prototype_id`. Values are borrowed, except overrides held behind a lock
+            /// (see `Storage::get_entity_cow`).
+            pub fn iter_field<'a, T: ::core::clone::Clone + 'a, S: $crate::Storage<T> + 'a>(
+                &'a self,
+                field: impl FnOnce(&'a Self) -> &'a S,
+            ) -> impl Iterator<Item = ::std::borrow::Cow<'a, T>> + 'a {
+                let storage = field(self);
+                self.prototype_id
+                    .iter()
+                    .enumerate()
+                    .map(move |(i, &proto)| storage.get_entity_cow(i, <$pid as ::core::convert::Into<usize>>::into(proto)))
+            }
+
+            /// Names of the fields where entities `a` and `b` have different effective values.
+            pub fn diff_entities(&self, a: usize, b: usize) -> ::std::vec::Vec<&'static str>
+            where
+                $(
+                    for<'h> $ty: ::core::cmp::PartialEq + ::core::clone::Clone,
+                )*
+            {
+                let (proto_a, proto_b) = (self.prototype_index(a), self.prototype_index(b));
+                let mut changed = ::std::vec::Vec::new();
+                $(
+                    if <_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, a, proto_a)
+                        != <_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, b, proto_b)
+                    {
+                        changed.push(::core::stringify!($field));
+                    }
+                )*
+                changed
+            }
+
+            /// Names of the fields where entity `entity_idx` differs from prototype `proto_idx`
+            /// of the `prototypes` table.
+            pub fn diff_entity_from_prototype(
+                &self,
+                entity_idx: usize,
+                prototypes: &$ecs,
+                proto_idx: usize,
+            ) -> ::std::vec::Vec<&'static str>
+            where
+                $(
+                    for<'h> $ty: ::core::cmp::PartialEq + ::core::clone::Clone,
+                )*
+            {
+                let entity_proto = self.prototype_index(entity_idx);
+                let mut changed = ::std::vec::Vec::new();
+                $(
+                    if *<_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, entity_idx, entity_proto)
+                        != *<_ as $crate::Storage<$ty>>::prototype(&prototypes.$field, proto_idx)
+                    {
+                        changed.push(::core::stringify!($field));
+                    }
+                )*
+                changed
+            }
+
+            /// Binary searches entities by a key derived from their index.
+            ///
+            /// Follows the `slice::binary_search_by_key` contract: `Ok(i)` if a matching entity
+            /// was found, otherwise `Err(i)` where `i` is the insertion point. The table must
+            /// already be sorted by the key (checked with a debug assertion).
+            pub fn binary_search_by_key<K: ::core::cmp::Ord>(
+                &self,
+                key: &K,
+                key_fn: impl Fn(usize) -> K,
+            ) -> ::core::result::Result<usize, usize> {
+                let len = self.prototype_id.len();
+
+                debug_assert!(
+                    (1..len).all(|i| key_fn(i - 1) <= key_fn(i)),
+                    "binary_search_by_key: table is not sorted by key"
+                );
+
+                let mut lo = 0;
+                let mut hi = len;
+                while lo < hi {
+                    let mid = lo + (hi - lo) / 2;
+                    match key_fn(mid).cmp(key) {
+                        ::core::cmp::Ordering::Less => lo = mid + 1,
+                        ::core::cmp::Ordering::Greater => hi = mid,
+                        ::core::cmp::Ordering::Equal => return ::core::result::Result::Ok(mid),
+                    }
+                }
+                ::core::result::Result::Err(lo)
+            }
+
+            /// Number of entities in this table (prototypes, when called on the prototype table).
+            pub fn prototype_count(&self) -> usize {
+                self.prototype_id.len()
+            }
+
+            /// Checks that every entity's `prototype_id` names a prototype in `prototypes`, e.g.
+            /// after loading a save file whose prototypes may have since been removed.
+            ///
+            /// On failure returns every `(entity_idx, invalid_proto_id)` pair.
+            pub fn validate_prototype_ids(&self, prototypes: &Self) -> ::core::result::Result<(), ::std::vec::Vec<(usize, usize)>> {
+                let prototype_count = prototypes.prototype_count();
+                let invalid: ::std::vec::Vec<(usize, usize)> = self
+                    .prototype_id
+                    .iter()
+                    .map(|&proto_id| <$pid as ::core::convert::Into<usize>>::into(proto_id))
+                    .enumerate()
+                    .filter(|&(_, proto_id)| proto_id >= prototype_count)
+                    .collect();
+                if invalid.is_empty() { Ok(()) } else { Err(invalid) }
+            }
+
+            /// Whether no two rows share a `prototype_id`. Always true for prototypes assigned by
+            /// `load_prototype`; a startup/test sanity check for tables filled some other way.
+            pub fn prototype_ids_are_unique(&self) -> bool {
+                let mut seen = ::std::collections::HashSet::with_capacity(self.prototype_id.len());
+                self.prototype_id
+                    .iter()
+                    .all(|&proto_id| seen.insert(<$pid as ::core::convert::Into<usize>>::into(proto_id)))
+            }
+
+            /// Whether every registered field has one row per entry of `prototype_id`: entity
+            /// rows for a runtime table (overlay instances), or prototype rows for a prototypes
+            /// table (overlay `dense_data`).
+            ///
+            /// Returns `false` rather than panicking so callers can log context; `spawn_entity`
+            /// and `swap_remove` `debug_assert!` it.
+            pub fn len_consistent(&self) -> bool {
+                let n = self.prototype_id.len();
+                let runtime = true
+                    $(&& <_ as $crate::Storage<$ty>>::entity_count(&self.$field).is_none_or(|c| c == n))*;
+                let prototypes = true
+                    $(&& <_ as $crate::Storage<$ty>>::prototype_row_count(&self.$field).is_none_or(|c| c == n))*;
+                runtime || prototypes
+            }
+
+            /// Whether every registered field holds the same number of rows, ignoring
+            /// `prototype_id`: all entity rows equal (runtime tables) or all prototype rows equal
+            /// (prototypes tables). A quick check for tests that doesn't panic.
+            pub fn field_lens_equal(&self) -> bool {
+                fn all_equal(lens: &[::core::option::Option<usize>]) -> bool {
+                    let mut lens = lens.iter().flatten();
+                    lens.next().is_none_or(|first| lens.all(|len| len == first))
+                }
+                all_equal(&[$(<_ as $crate::Storage<$ty>>::entity_count(&self.$field)),*])
+                    || all_equal(&[$(<_ as $crate::Storage<$ty>>::prototype_row_count(&self.$field)),*])
+            }
+
+            /// Number of entities in this table.
+            pub fn len(&self) -> usize {
+                self.prototype_id.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.prototype_id.is_empty()
+            }
+
+            /// Indices of every entity, `0..self.len()`.
+            pub fn index_iter(&self) -> ::core::ops::Range<usize> {
+                0..self.len()
+            }
+
+            /// Entity indices grouped by prototype: `result[p]` lists every entity with
+            /// `prototype_id == p`, ascending. There is one group per prototype of `prototypes`,
+            /// empty for prototypes with no entities.
+            pub fn group_by_prototype(&self, prototypes: &Self) -> ::std::vec::Vec<::std::vec::Vec<usize>> {
+                $crate::group_by_prototype_ids(
+                    &$crate::__prototype_ids_as_usize(&self.prototype_id),
+                    prototypes.prototype_count(),
+                )
+            }
+
+            /// Like `group_by_prototype`, but only computed on first use and then cached.
+            ///
+            /// The cache borrows the table, so it can't outlive a structural change
+            /// (`spawn_entity`, `swap_remove`, ...); build a new one afterwards.
+            pub fn group_by_prototype_lazy(&self, prototypes: &Self) -> $crate::GroupByPrototype<'_> {
+                $crate::GroupByPrototype::new(
+                    $crate::__prototype_ids_as_usize(&self.prototype_id),
+                    prototypes.prototype_count(),
+                )
+            }
+
+            /// Splits `0..self.len()` into consecutive non-overlapping ranges of `chunk_size`
+            /// (the last may be shorter), e.g. for handing work to a thread pool.
+            pub fn iter_entity_chunks(
+                &self,
+                chunk_size: usize,
+            ) -> impl ::core::iter::Iterator<Item = ::core::ops::Range<usize>> + use<> {
+                assert!(chunk_size > 0, "iter_entity_chunks: chunk_size must be non-zero");
+                let len = self.len();
+                (0..len)
+                    .step_by(chunk_size)
+                    .map(move |start| start..(start + chunk_size).min(len))
+            }
+
+            /// Calls `f(i, self, other)` for every entity index of `self`, collecting the results.
+            ///
+            /// `other` can be any table (e.g. physics alongside transforms) that has at least
+            /// `self.len()` entities, indexed the same way.
+            pub fn zip_with<O, R>(
+                &self,
+                other: &O,
+                mut f: impl FnMut(usize, &Self, &O) -> R,
+            ) -> ::std::vec::Vec<R> {
+                self.index_iter().map(|i| f(i, self, other)).collect()
+            }
+
+            /// Calls `f(self, i)` for every entity index, so a system can mutate the table
+            /// without an explicit index loop.
+            ///
+            /// The entity count is read once up front: entities spawned by `f` aren't visited,
+            /// and `f` must not remove entities. Borrowing two fields of `self` at once inside
+            /// `f` needs the same workarounds (split borrows, `zip_with`) as plain code.
+            pub fn apply_system(&mut self, mut f: impl FnMut(&mut Self, usize)) {
+                for i in 0..self.len() {
+                    f(self, i);
+                }
+            }
+
+            /// Like `apply_system`, but only calls `f` for entities where `pred(self, i)` holds.
+            pub fn filter_system(
+                &mut self,
+                pred: impl Fn(&Self, usize) -> bool,
+                mut f: impl FnMut(&mut Self, usize),
+            ) {
+                for i in 0..self.len() {
+                    if pred(self, i) {
+                        f(self, i);
+                    }
+                }
+            }
+
+            /// Exports one prototype as a JSON object keyed by field name.
+            ///
+            /// `prototype_id` is not included since it is re-assigned by `load_prototype`.
+            pub fn export_prototype_to_json(&self, prototype_index: usize) -> ::serde_json::Value
+            where
+                $(
+                    for<'h> $ty: ::serde::Serialize,
+                )*
+            {
+                let mut obj = ::serde_json::Map::new();
+                $(
+                    obj.insert(
+                        ::core::stringify!($field).to_string(),
+                        <_ as $crate::Storage<$ty>>::export_json(&self.$field, prototype_index),
+                    );
+                )*
+                ::serde_json::Value::Object(obj)
+            }
+
+            /// Clones every registered field of prototype `proto_idx` into a tuple, in
+            /// declaration order, so a whole prototype can be handed to code that doesn't know
+            /// the table's layout.
+            ///
+            /// A tuple rather than a named snapshot struct, since `macro_rules` can't build a new
+            /// type name from the table's. `prototype_id` is not included.
+            pub fn prototype_as_snapshot(&self, proto_idx: usize) -> ($($ty,)*)
+            where
+                $(
+                    for<'h> $ty: ::core::clone::Clone,
+                )*
+            {
+                ($(
+                    <_ as $crate::Storage<$ty>>::prototype(&self.$field, proto_idx).clone(),
+                )*)
+            }
+
+            /// Exports one live entity's effective values as a JSON object keyed by field name.
+            ///
+            /// The result has the same shape as a prototype, so it can be fed to
+            /// `load_prototype` (e.g. copy an entity to the clipboard as a new prototype).
+            pub fn entity_to_json(&self, idx: usize) -> ::serde_json::Value
+            where
+                $(
+                    for<'h> $ty: ::serde::Serialize,
+                )*
+            {
+                let prototype_id = self.prototype_index(idx);
+                let mut obj = ::serde_json::Map::new();
+                $(
+                    obj.insert(
+                        ::core::stringify!($field).to_string(),
+                        <_ as $crate::Storage<$ty>>::get_as_json(&self.$field, idx, prototype_id),
+                    );
+                )*
+                ::serde_json::Value::Object(obj)
+            }
+
+            /// Serializes just the entities at `indices` (e.g. players, not background NPCs):
+            /// one object per entity with its `index`, `prototype_id`, and the `fields` it doesn't
+            /// share with its prototype (dense fields, plus overlay fields with an override).
+            ///
+            /// `restore_entities` writes a snapshot back.
+            pub fn snapshot_entities(&self, indices: &[usize]) -> $crate::EntitySnapshot
+            where
+                $(
+                    for<'h> $ty: ::serde::Serialize + ::serde::de::DeserializeOwned,
+                )*
+            {
+                indices
+                    .iter()
+                    .map(|&idx| {
+                        let prototype_id = self.prototype_index(idx);
+                        let mut fields = ::serde_json::Map::new();
+                        $(
+                            if <_ as $crate::Storage<$ty>>::override_state(&self.$field, idx)
+                                != ::core::option::Option::Some(false)
+                            {
+                                fields.insert(
+                                    ::core::stringify!($field).to_string(),
+                                    <_ as $crate::Storage<$ty>>::get_as_json(&self.$field, idx, prototype_id),
+                                );
+                            }
+                        )*
+                        ::serde_json::json!({
+                            "index": idx,
+                            "prototype_id": prototype_id,
+                            "fields": fields,
+                        })
+                    })
+                    .collect()
+            }
+
+            /// Writes a `snapshot_entities` snapshot back into the same entity indices, in place:
+            /// each entity gets the snapshot's prototype_id and field values, and overlay fields
+            /// missing from the snapshot go back to reading the prototype.
+            ///
+            /// Panics on a malformed snapshot or an index past the end of the table.
+            pub fn restore_entities(&mut self, snapshot: $crate::EntitySnapshot)
+            where
+                $(
+                    for<'h> $ty: ::serde::Serialize + ::serde::de::DeserializeOwned,
+                )*
+            {
+                for entity in snapshot {
+                    let idx = entity["index"].as_u64().expect("snapshot entity has no index") as usize;
+                    let prototype_id = entity["prototype_id"]
+                        .as_u64()
+                        .expect("snapshot entity has no prototype_id") as usize;
+                    self.prototype_id[idx] = ::core::convert::From::from(prototype_id);
+                    $(
+                        match entity["fields"].get(::core::stringify!($field)) {
+                            ::core::option::Option::Some(v) => <_ as $crate::Storage<$ty>>::set_entity(
+                                &mut self.$field,
+                                idx,
+                                ::serde_json::from_value(v.clone())
+                                    .expect("snapshot field doesn't match the field type"),
+                            ),
+                            ::core::option::Option::None => {
+                                <_ as $crate::Storage<$ty>>::clear_entity_override(&mut self.$field, idx)
+                            }
+                        }
+                    )*
+                }
+            }
+
+            /// Exports every prototype as a JSON array, the inverse of `load_prototype_batch`.
+            pub fn to_json_array(&self) -> ::serde_json::Value
+            where
+                $(
+                    for<'h> $ty: ::serde::Serialize,
+                )*
+            {
+                ::serde_json::Value::Array(
+                    (0..self.prototype_count())
+                        .map(|i| self.export_prototype_to_json(i))
+                        .collect(),
+                )
+            }
+
+            /// Prints one entity's prototype_id and every registered field, for debugging.
+            ///
+            /// Overlay fields also show whether the entity has its own override.
+            pub fn debug_print_entity(&self, idx: usize)
+            where
+                $(
+                    for<'h> $ty: ::core::fmt::Debug + ::core::clone::Clone,
+                )*
+            {
+                let prototype_id = self.prototype_index(idx);
+                println!("entity: {idx}");
+                println!("prototype_id: {prototype_id}");
+                $(
+                    let value = <_ as $crate::Storage<$ty>>::get_entity_cow(&self.$field, idx, prototype_id);
+                    match <_ as $crate::Storage<$ty>>::override_state(&self.$field, idx) {
+                        ::core::option::Option::Some(has_override) => println!(
+                            "{}: {:?} (override={})",
+                            ::core::stringify!($field),
+                            value,
+                            has_override
+                        ),
+                        ::core::option::Option::None => {
+                            println!("{}: {:?}", ::core::stringify!($field), value)
+                        }
+                    }
+                )*
+            }
+
+            /// Renders every entity as a text table for a debug console: a header of field
+            /// names, then one `Debug`-formatted row per entity. Overlay values with an override
+            /// are marked with a trailing `*`. Not meant for anything performance-sensitive.
+            pub fn to_tabular_string(&self) -> ::std::string::String
+            where
+                $(
+                    for<'h> $ty: ::core::fmt::Debug + ::core::clone::Clone,
+                )*
+            {
+                let mut columns: ::std::vec::Vec<(&str, ::std::vec::Vec<::std::string::String>)> =
+                    ::std::vec![("prototype_id", self.index_iter().map(|i| self.prototype_index(i).to_string()).collect())];
+                $(
+                    columns.push((
+                        ::core::stringify!($field),
+                        self.index_iter()
+                            .map(|i| {
+                                let value = <_ as $crate::Storage<$ty>>::get_entity_cow(
+                                    &self.$field,
+                                    i,
+                                    self.prototype_index(i),
+                                );
+                                let mut cell = ::std::format!("{value:?}");
+                                if <_ as $crate::Storage<$ty>>::override_state(&self.$field, i)
+                                    == ::core::option::Option::Some(true)
+                                {
+                                    cell.push('*');
+                                }
+                                cell
+                            })
+                            .collect(),
+                    ));
+                )*
+
+                let widths: ::std::vec::Vec<usize> = columns
+                    .iter()
+                    .map(|(name, cells)| cells.iter().map(|c| c.len()).fold(name.len(), usize::max))
+                    .collect();
+                let mut out = ::std::string::String::new();
+                let mut push_row = |cells: &mut dyn ::core::iter::Iterator<Item = &str>| {
+                    let line: ::std::vec::Vec<::std::string::String> = cells
+                        .zip(&widths)
+                        .map(|(cell, width)| ::std::format!("{cell:<width$}"))
+                        .collect();
+                    out.push_str(line.join(" | ").trim_end());
+                    out.push('\n');
+                };
+                push_row(&mut columns.iter().map(|(name, _)| *name));
+                for i in self.index_iter() {
+                    push_row(&mut columns.iter().map(|(_, cells)| cells[i].as_str()));
+                }
+                out
+            }
+
+            /// Appends prototypes from one already-deserialized slice per registered field,
+            /// skipping JSON entirely. All slices must have the same length.
+            #[allow(clippy::too_many_arguments, clippy::disallowed_names)]
+            pub fn load_prototypes_from_slices(&mut self, $($field: &[$ty]),*)
+            where
+                $(
+                    for<'h> $ty: ::core::clone::Clone,
+                )*
+            {
+                let lens = [$($field.len()),*];
+                let count = lens.first().copied().unwrap_or(0);
+                assert!(
+                    lens.iter().all(|&len| len == count),
+                    "load_prototypes_from_slices: slice lengths differ: {lens:?}"
+                );
+
+                $(
+                    <_ as $crate::Storage<$ty>>::push_prototypes_from_slice(&mut self.$field, $field);
+                )*
+
+                let start = self.prototype_id.len();
+                for next_id in start..start + count {
+                    self.prototype_id.push(::core::convert::From::from(next_id));
+                    $($(
+                        $crate::__impl_load_prototype_attr!(@load $attr self, next_id, $field : $ty);
+                    )?)*
+                }
+            }
+
+            /// Appends a copy of prototype `proto_idx` as a new prototype (e.g. the basis for an
+            /// elite variant), returning the new prototype_id.
+            ///
+            /// The copy isn't added to an `[index_by]` index, since it still has the original's
+            /// name.
+            pub fn clone_prototype(&mut self, proto_idx: usize) -> usize
+            where
+                $(
+                    for<'h> $ty: ::core::clone::Clone,
+                )*
+            {
+                $(
+                    let value = <_ as $crate::Storage<$ty>>::prototype(&self.$field, proto_idx).clone();
+                    <_ as $crate::Storage<$ty>>::push_prototypes_from_slice(
+                        &mut self.$field,
+                        ::core::slice::from_ref(&value),
+                    );
+                )*
+                let next_id = self.prototype_id.len();
+                self.prototype_id.push(::core::convert::From::from(next_id));
+                next_id
+            }
+
+            /// Swaps prototypes `a` and `b` in the prototypes table (e.g. to sort it by name).
+            ///
+            /// Runtime tables built from this one must then call `remap_swapped_prototypes(a, b)`
+            /// so their live entities keep pointing at the same prototype data.
+            pub fn swap_prototypes(&mut self, a: usize, b: usize) {
+                $(
+                    <_ as $crate::Storage<$ty>>::swap_prototypes(&mut self.$field, a, b);
+                )*
+                $($(
+                    $crate::__impl_load_prototype_attr!(@swap $attr self, a, b, $field : $ty);
+                )?)*
+            }
+
+            /// Runtime-table side of `swap_prototypes`: swaps the table's prototype copies
+            /// (overlay fields) and rewrites every live entity's prototype_id `a` <-> `b`.
+            pub fn remap_swapped_prototypes(&mut self, a: usize, b: usize) {
+                $(
+                    <_ as $crate::Storage<$ty>>::swap_prototype_copies(&mut self.$field, a, b);
+                )*
+                for proto in &mut self.prototype_id {
+                    let id = <$pid as ::core::convert::Into<usize>>::into(*proto);
+                    if id == a {
+                        *proto = ::core::convert::From::from(b);
+                    } else if id == b {
+                        *proto = ::core::convert::From::from(a);
+                    }
+                }
+            }
+
+            /// Multiplies every prototype's `field_name` value by `factor` (rounding integer fields),
+            /// e.g. for balance tuning. Non-numeric values (and non-numeric parts of structs) are
+            /// left alone.
+            ///
+            /// Call on the prototypes table; runtime tables built from it must then call
+            /// `scale_prototype_field_copies` so overlay fields pick up the new values. Entities
+            /// with an override, and dense fields of already-spawned entities, keep their values.
+            /// Returns `Err(UnknownFieldError)`, changing nothing, if `field_name` isn't a
+            /// registered field.
+            pub fn scale_prototype_field(
+                &mut self,
+                field_name: &str,
+                factor: f64,
+            ) -> ::core::result::Result<(), $crate::UnknownFieldError>
+            where
+                $(
+                    for<'h> $ty: ::serde::Serialize + ::serde::de::DeserializeOwned,
+                )*
+            {
+                match field_name {
+                    $(
+                        ::core::stringify!($field) => {
+                            <_ as $crate::Storage<$ty>>::transform_prototypes(
+                                &mut self.$field,
+                                &|value: &$ty| Self::scale_value(value, factor),
+                            );
+                        }
+                    )*
+                    _ => {
+                        return ::core::result::Result::Err($crate::UnknownFieldError {
+                            field: field_name.to_string(),
+                        });
+                    }
+                }
+                ::core::result::Result::Ok(())
+            }
+
+            /// Runtime-table side of `scale_prototype_field`: scales the table's prototype copies.
+            pub fn scale_prototype_field_copies(
+                &mut self,
+                field_name: &str,
+                factor: f64,
+            ) -> ::core::result::Result<(), $crate::UnknownFieldError>
+            where
+                $(
+                    for<'h> $ty: ::serde::Serialize + ::serde::de::DeserializeOwned,
+                )*
+            {
+                match field_name {
+                    $(
+                        ::core::stringify!($field) => {
+                            <_ as $crate::Storage<$ty>>::transform_prototype_copies(
+                                &mut self.$field,
+                                &|value: &$ty| Self::scale_value(value, factor),
+                            );
+                        }
+                    )*
+                    _ => {
+                        return ::core::result::Result::Err($crate::UnknownFieldError {
+                            field: field_name.to_string(),
+                        });
+                    }
+                }
+                ::core::result::Result::Ok(())
+            }
+
+            /// Scales `value` through its JSON form (see `scale_json_number`).
+            fn scale_value<V>(value: &V, factor: f64) -> V
+            where
+                V: ::serde::Serialize + ::serde::de::DeserializeOwned,
+            {
+                let json = ::serde_json::to_value(value).expect("failed to serialize field");
+                let scaled = match json {
+                    ::serde_json::Value::Object(map) => ::serde_json::Value::Object(
+                        map.into_iter()
+                            .map(|(k, v)| (k, $crate::scale_json_number(&v, factor)))
+                            .collect(),
+                    ),
+                    other => $crate::scale_json_number(&other, factor),
+                };
+                ::serde_json::from_value(scaled).expect("scaled value doesn't fit the field type")
+            }
+
+            /// Returns the first key of a prototype JSON object that isn't a registered field.
+            fn check_unknown_fields(
+                prototype: &::serde_json::Value,
+            ) -> ::core::result::Result<(), $crate::UnknownFieldError> {
+                let obj = prototype
+                    .as_object()
+                    .expect("prototype must be a JSON object");
+                for key in obj.keys() {
+                    if key != "prototype_id" && !Self::component_names().contains(&key.as_str()) {
+                        return ::core::result::Result::Err($crate::UnknownFieldError {
+                            field: key.clone(),
+                        });
+                    }
+                }
+                ::core::result::Result::Ok(())
+            }
+
+            /// Loads a prototype from a JSON object into the ECS, ignoring unknown keys.
+            fn load_prototype_fields(&mut self, prototype: ::serde_json::Value) {
+                let obj = prototype
+                    .as_object()
+                    .expect("prototype must be a JSON object");
+                let null = ::serde_json::Value::Null;
+
+                $(
+                    if let Some(v) = obj.get(::core::stringify!($field)) {
+                        <_ as $crate::Storage<$ty>>::push_json(&mut self.$field, v);
+                    } else {
+                        // Missing field => default prototype value, regardless of storage backend.
+                        <_ as $crate::Storage<$ty>>::push_json(&mut self.$field, &null);
+                    }
+                )*
+
+                // Auto-assign prototype_id if the JSON didn't include it (or if it did; we ignore it).
+                let next_id = self.prototype_id.len();
+                self.prototype_id.push(::core::convert::From::from(next_id));
+
+                $($(
+                    $crate::__impl_load_prototype_attr!(@load $attr self, next_id, $field : $ty);
+                )?)*
+            }
+
+            /// Loads a prototype from environment variables named `{prefix}_{FIELD}` (field name
+            /// upper-cased), returning its prototype id. Twelve-factor style configuration.
+            ///
+            /// Each variable is parsed as JSON; if that doesn't give a valid value for the field,
+            /// the raw text is used as a JSON string (so `NAME=goblin` works for `String`
+            /// fields). Missing variables use the field's default.
+            pub fn load_prototype_from_env(&mut self, prefix: &str) -> usize
+            where
+                $(
+                    for<'h> $ty: ::serde::de::DeserializeOwned,
+                )*
+            {
+                let mut obj = ::serde_json::Map::new();
+                $(
+                    let key = ::std::format!(
+                        "{prefix}_{}",
+                        ::core::stringify!($field).to_ascii_uppercase()
+                    );
+                    if let ::core::result::Result::Ok(raw) = ::std::env::var(&key) {
+                        let value = match ::serde_json::from_str::<::serde_json::Value>(&raw) {
+                            ::core::result::Result::Ok(v)
+                                if ::serde_json::from_value::<$ty>(v.clone()).is_ok() => v,
+                            _ => ::serde_json::Value::String(raw),
+                        };
+                        obj.insert(::core::stringify!($field).to_string(), value);
+                    }
+                )*
+                self.load_prototype_fields(::serde_json::Value::Object(obj));
+                self.prototype_count() - 1
+            }
+
+            /// Loads a prototype from a JSON object into row `id`, ignoring unknown keys.
+            ///
+            /// Missing rows before `id` are filled with default prototypes; an existing row `id`
+            /// is overwritten.
+            fn load_prototype_fields_at(&mut self, prototype: ::serde_json::Value, id: usize) {
+                if cfg!(debug_assertions) && id != self.prototype_count() {
+                    ::std::eprintln!(
+                        "load_prototype_with_id: prototype {id} loaded out of order (next id is {})",
+                        self.prototype_count()
+                    );
+                }
+                while self.prototype_count() < id {
+                    self.load_prototype_fields(::serde_json::Value::Object(::serde_json::Map::new()));
+                }
+                if id == self.prototype_count() {
+                    self.load_prototype_fields(prototype);
+                    return;
+                }
+
+                let obj = prototype
+                    .as_object()
+                    .expect("prototype must be a JSON object");
+                let null = ::serde_json::Value::Null;
+                $($(
+                    $crate::__impl_load_prototype_attr!(@unload $attr self, id, $field : $ty);
+                )?)*
+                $(
+                    <_ as $crate::Storage<$ty>>::replace_json(
+                        &mut self.$field,
+                        id,
+                        obj.get(::core::stringify!($field)).unwrap_or(&null),
+                    );
+                )*
+                $($(
+                    $crate::__impl_load_prototype_attr!(@load $attr self, id, $field : $ty);
+                )?)*
+            }
+        }
+    };
+
+    // Explicit error for old macro call sites that don't declare prototype_id.
+    (@impl $mode:ident $ecs:ty { $($field:ident : $ty:ty $([$attr:ident])?),* $(,)? } $(unregistered $unregistered:tt)?) => {
+        compile_error!(
+            "impl_load_prototype!(...) now requires `prototype_id: usize` (or another `Copy + Into<usize> + From<usize>` id type) as the first field in the macro invocation, so prototype IDs can be auto-assigned and copied on spawn."
+        );
+    };
+
+    (@impl $($rest:tt)*) => {
+        compile_error!("impl_load_prototype!: expected `MySoA { prototype_id: usize, field: Type, ... }`");
+    };
+
+    ($($rest:tt)*) => {
+        $crate::impl_load_prototype!(@impl allow_unknown_fields $($rest)*);
+    };
+}
+
+// `load_prototype` / `load_prototype_batch` for each `#[soa(...)]` unknown-field mode.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_load_prototype_mode {
+    (allow_unknown_fields) => {
+        /// Loads a prototype from a JSON object into the ECS.
+        ///
+        /// Keys that aren't registered fields are ignored.
+        pub fn load_prototype(&mut self, prototype: ::serde_json::Value) {
+            self.load_prototype_fields(prototype);
+        }
+
+        /// Loads every prototype in a JSON array, in order.
+        pub fn load_prototype_batch(&mut self, prototypes: ::serde_json::Value) {
+            let arr = match prototypes {
+                ::serde_json::Value::Array(arr) => arr,
+                _ => panic!("prototype batch must be a JSON array"),
+            };
+            for prototype in arr {
+                self.load_prototype(prototype);
+            }
+        }
+
+        /// Loads a prototype into row `id` instead of the next free row (e.g. from a save file
+        /// whose prototype ids must be preserved).
+        ///
+        /// Missing rows before `id` are filled with default prototypes and an existing row `id`
+        /// is overwritten. Debug builds warn when `id` isn't the next free row, to catch
+        /// accidental out-of-order loading.
+        pub fn load_prototype_with_id(&mut self, prototype: ::serde_json::Value, id: usize) {
+            self.load_prototype_fields_at(prototype, id);
+        }
+    };
+    (deny_unknown_fields) => {
+        /// Loads a prototype from a JSON object into the ECS.
+        ///
+        /// Fails without loading anything if the object has a key that isn't a registered field.
+        pub fn load_prototype(
+            &mut self,
+            prototype: ::serde_json::Value,
+        ) -> ::core::result::Result<(), $crate::UnknownFieldError> {
+            Self::check_unknown_fields(&prototype)?;
+            self.load_prototype_fields(prototype);
+            ::core::result::Result::Ok(())
+        }
+
+        /// Loads every prototype in a JSON array, in order, stopping at the first unknown field.
+        pub fn load_prototype_batch(
+            &mut self,
+            prototypes: ::serde_json::Value,
+        ) -> ::core::result::Result<(), $crate::UnknownFieldError> {
+            let arr = match prototypes {
+                ::serde_json::Value::Array(arr) => arr,
+                _ => panic!("prototype batch must be a JSON array"),
+            };
+            for prototype in arr {
+                self.load_prototype(prototype)?;
+            }
+            ::core::result::Result::Ok(())
+        }
+
+        /// Loads a prototype into row `id` instead of the next free row (e.g. from a save file
+        /// whose prototype ids must be preserved).
+        ///
+        /// Missing rows before `id` are filled with default prototypes and an existing row `id`
+        /// is overwritten. Fails without loading anything on an unknown field. Debug builds warn
+        /// when `id` isn't the next free row, to catch accidental out-of-order loading.
+        pub fn load_prototype_with_id(
+            &mut self,
+            prototype: ::serde_json::Value,
+            id: usize,
+        ) -> ::core::result::Result<(), $crate::UnknownFieldError> {
+            Self::check_unknown_fields(&prototype)?;
+            self.load_prototype_fields_at(prototype, id);
+            ::core::result::Result::Ok(())
+        }
+    };
+    ($mode:ident) => {
+        compile_error!(::core::concat!(
+            "impl_load_prototype!: unknown option `#[soa(",
+            ::core::stringify!($mode),
+            ")]`"
+        ));
+    };
+}
+
+// `json_schema()` for `impl_load_prototype!`, only with the `json_schema` feature. The cfg has to
+// live here: a `#[cfg(feature = ...)]` inside the main macro would check the caller's features.
+#[cfg(feature = "json_schema")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_json_schema {
+    ($($field:ident : $ty:ty),*) => {
+        /// JSON Schema (draft 7) describing a prototype object of this table.
+        ///
+        /// Each field's schema is inferred from the JSON of its `Default` value, so e.g. an
+        /// `Option` that defaults to `None` is left unconstrained.
+        pub fn json_schema() -> ::serde_json::Value
+        where
+            $(
+                for<'h> $ty: ::core::default::Default + ::serde::Serialize,
+            )*
+        {
+            let mut properties = ::serde_json::Map::new();
+            $(
+                let default = ::serde_json::to_value(<$ty as ::core::default::Default>::default())
+                    .expect("failed to serialize default field value");
+                properties.insert(
+                    ::core::stringify!($field).to_string(),
+                    $crate::json_schema_for_value(&default),
+                );
+            )*
+            ::serde_json::json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": properties,
+            })
+        }
+    };
+}
+
+#[cfg(not(feature = "json_schema"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_json_schema {
+    ($($field:ident : $ty:ty),*) => {};
+}
+
+// Compile-time "every struct field is registered or listed as unregistered" check. A struct
+// literal without `..` fails to compile with "missing field `x`" if it doesn't mention a field.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_field_check {
+    (($($field:ident),*) { $($unregistered:ident),* $(,)? }) => {
+        #[allow(dead_code)]
+        fn assert_all_fields_registered(self) -> Self {
+            Self {
+                prototype_id: self.prototype_id,
+                $($field: self.$field,)*
+                $($unregistered: self.$unregistered,)*
+            }
+        }
+    };
+    (($($field:ident),*)) => {};
+}
+
+// Per-field `[attr]` expansions for `impl_load_prototype!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_load_prototype_attr {
+    (@methods index_by $field:ident : $ty:ty) => {
+        /// Looks up a prototype_id by the value of the `[index_by]` field.
+        pub fn prototype_by_name(&self, name: &str) -> ::core::option::Option<usize> {
+            self.name_index.get(name).copied()
+        }
+    };
+    (@load index_by $self:ident, $id:ident, $field:ident : $ty:ty) => {
+        let name = ::std::string::ToString::to_string(
+            <_ as $crate::Storage<$ty>>::prototype(&$self.$field, $id),
+        );
+        $self.name_index.insert(name, $id);
+    };
+    (@unload index_by $self:ident, $id:ident, $field:ident : $ty:ty) => {
+        let name = ::std::string::ToString::to_string(
+            <_ as $crate::Storage<$ty>>::prototype(&$self.$field, $id),
+        );
+        if $self.name_index.get(&name) == ::core::option::Option::Some(&$id) {
+            $self.name_index.remove(&name);
+            // Another prototype with the same name takes over the entry.
+            let other = (0..$self.prototype_count()).rev().find(|&proto| {
+                proto != $id
+                    && ::std::string::ToString::to_string(
+                        <_ as $crate::Storage<$ty>>::prototype(&$self.$field, proto),
+                    ) == name
+            });
+            if let ::core::option::Option::Some(other) = other {
+                $self.name_index.insert(name, other);
+            }
+        }
+    };
+    (@swap index_by $self:ident, $a:ident, $b:ident, $field:ident : $ty:ty) => {
+        for proto in $self.name_index.values_mut() {
+            if *proto == $a {
+                *proto = $b;
+            } else if *proto == $b {
+                *proto = $a;
+            }
+        }
+    };
+    (@$stage:ident $attr:ident $($rest:tt)*) => {
+        compile_error!(::core::concat!(
+            "impl_load_prototype!: unknown field attribute `[",
+            ::core::stringify!($attr),
+            "]`"
+        ));
+    };
+}
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Index;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Index of a live entity (a row of a runtime table).
+///
+/// `Overlay`'s accessors take `impl Into<InstanceId>` and `impl Into<PrototypeId>`, so an
+/// instance id can't be passed where a prototype id is expected, or vice versa. Plain `usize`s
+/// still convert into either, so untyped call sites keep compiling while they migrate.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InstanceId(pub usize);
+
+/// Index of a prototype (a row of the prototypes table).
+///
+/// Also usable as an `impl_load_prototype!` table's `prototype_id` column type, so the ids
+/// read from the column are already typed.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PrototypeId(pub usize);
+
+impl From<usize> for InstanceId {
+    fn from(id: usize) -> Self {
+        InstanceId(id)
+    }
+}
+
+impl From<InstanceId> for usize {
+    fn from(id: InstanceId) -> Self {
+        id.0
+    }
+}
+
+impl From<usize> for PrototypeId {
+    fn from(id: usize) -> Self {
+        PrototypeId(id)
+    }
+}
+
+impl From<PrototypeId> for usize {
+    fn from(id: PrototypeId) -> Self {
+        id.0
+    }
+}
+
+/// Returned by `load_prototype` under `#[soa(deny_unknown_fields)]` when the prototype JSON has
+/// a key that isn't a registered field (usually a typo).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFieldError {
+    pub field: String,
+}
+
+impl fmt::Display for UnknownFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown prototype field `{}`", self.field)
+    }
+}
+
+impl std::error::Error for UnknownFieldError {}
+
+/// Infers a JSON Schema (draft 7) from an example value, recursing into objects and arrays.
+#[cfg(feature = "json_schema")]
+pub fn json_schema_for_value(value: &Value) -> Value {
+    match value {
+        Value::Null => serde_json::json!({}),
+        Value::Bool(_) => serde_json::json!({ "type": "boolean" }),
+        Value::Number(n) if n.is_f64() => serde_json::json!({ "type": "number" }),
+        Value::Number(_) => serde_json::json!({ "type": "integer" }),
+        Value::String(_) => serde_json::json!({ "type": "string" }),
+        Value::Array(items) => match items.first() {
+            Some(first) => serde_json::json!({ "type": "array", "items": json_schema_for_value(first) }),
+            None => serde_json::json!({ "type": "array" }),
+        },
+        Value::Object(obj) => {
+            let properties: serde_json::Map<String, Value> = obj
+                .iter()
+                .map(|(k, v)| (k.clone(), json_schema_for_value(v)))
+                .collect();
+            serde_json::json!({ "type": "object", "properties": properties })
+        }
+    }
+}
+
+/// Multiplies a JSON number by `factor`, rounding if it was an integer. Other values are
+/// returned unchanged (see the generated `scale_prototype_field`).
+pub fn scale_json_number(value: &Value, factor: f64) -> Value {
+    if let Some(n) = value.as_i64() {
+        Value::from((n as f64 * factor).round() as i64)
+    } else if let Some(n) = value.as_u64() {
+        Value::from((n as f64 * factor).round() as u64)
+    } else if let Some(n) = value.as_f64() {
+        Value::from(n * factor)
+    } else {
+        value.clone()
+    }
+}
+
+/// Applies an RFC 7396 JSON merge patch to `target`: object patches merge key by key
+/// (recursively), a `null` member deletes that key, and any other patch replaces `target`.
+pub fn json_merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(serde_json::Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!("json_merge_patch: target was just made an object");
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            json_merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// A selective entity save from the generated `snapshot_entities`: one JSON object per entity,
+/// `{ "index", "prototype_id", "fields": { .. } }`.
+pub type EntitySnapshot = Vec<Value>;
+
+/// Groups entity indices by their prototype id into `prototype_count` groups (see the
+/// generated `group_by_prototype`).
+///
+/// Panics if an id is `>= prototype_count`.
+pub fn group_by_prototype_ids(prototype_ids: &[usize], prototype_count: usize) -> Vec<Vec<usize>> {
+    let mut groups = vec![Vec::new(); prototype_count];
+    for (entity, &proto) in prototype_ids.iter().enumerate() {
+        assert!(
+            proto < prototype_count,
+            "group_by_prototype: entity {entity} has prototype {proto} out of range ({prototype_count} prototypes)"
+        );
+        groups[proto].push(entity);
+    }
+    groups
+}
+
+/// Lazily computed, cached `group_by_prototype` over a borrowed `prototype_id` column.
+pub struct GroupByPrototype<'a> {
+    prototype_ids: Cow<'a, [usize]>,
+    prototype_count: usize,
+    groups: OnceLock<Vec<Vec<usize>>>,
+}
+
+impl<'a> GroupByPrototype<'a> {
+    pub fn new(prototype_ids: impl Into<Cow<'a, [usize]>>, prototype_count: usize) -> Self {
+        Self {
+            prototype_ids: prototype_ids.into(),
+            prototype_count,
+            groups: OnceLock::new(),
+        }
+    }
+
+    /// All groups, computing them on first call.
+    pub fn groups(&self) -> &[Vec<usize>] {
+        self.groups.get_or_init(|| group_by_prototype_ids(&self.prototype_ids, self.prototype_count))
+    }
+
+    /// Entities of prototype `proto_id` (empty if there are none).
+    pub fn get(&self, proto_id: usize) -> &[usize] {
+        self.groups().get(proto_id).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// The secret sauce: A trait that masks the difference between Vec and Overlay
+pub trait Storage<T> {
+    /// Initialize a runtime table field from the prototypes table.
+    ///
+    /// - For dense `Vec<T>`: no-op (runtime instances start empty).
+    /// - For `Overlay<T>`: clones prototype `dense_data` so runtime lookups can fall back.
+    fn init_from_prototypes(&mut self, prototypes: &Self);
+    fn push_json(&mut self, json: &Value);
+    fn push_from_prototype(&mut self, source: &Self, proto_idx: usize);
+
+    /// Overwrites prototype row `proto_idx` from JSON, like `push_json` does for a new row
+    /// (called on the prototypes table).
+    fn replace_json(&mut self, proto_idx: usize, json: &Value);
+
+    /// Appends an entity holding `T::default()` instead of a prototype value.
+    ///
+    /// Overlays store the default as an override, so it wins over the prototype on reads.
+    fn push_default(&mut self);
+
+    fn swap_remove(&mut self, index: usize);
+
+    /// Prototype/template value at `proto_idx` (a row of the prototypes table).
+    fn prototype(&self, proto_idx: usize) -> &T;
+
+    /// Effective value of entity `index`, whose prototype is `proto_idx`.
+    ///
+    /// Panics for an overridden entity of storage whose overrides sit behind a lock
+    /// (`AtomicOverlay`, `RwOverlay`); use `get_entity_cow` where that storage can appear.
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T;
+
+    /// Effective value of entity `index`: borrowed where the storage can lend it, a clone for
+    /// overrides behind a lock. The generated readers use this, so they work for every storage.
+    fn get_entity_cow(&self, index: usize, proto_idx: usize) -> Cow<'_, T>
+    where
+        T: Clone,
+    {
+        Cow::Borrowed(self.get_entity(index, proto_idx))
+    }
+
+    /// Whether entity `index` has its own override, or `None` for dense storage (the
+    /// default).
+    fn override_state(&self, _index: usize) -> Option<bool> {
+        None
+    }
+
+    /// Makes entity `index` keep its current value from prototype `proto_idx` even if its
+    /// prototype changes (copy-on-write for overlays).
+    ///
+    /// No-op by default: dense values were copied at spawn and are already per-entity.
+    fn pin_entity(&mut self, _index: usize, _proto_idx: usize) {}
+
+    /// Sets entity `index`'s value (an override, for overlays).
+    fn set_entity(&mut self, index: usize, value: T);
+
+    /// Drops entity `index`'s override so it reads its prototype again.
+    ///
+    /// No-op for dense storage, whose values are always per-entity.
+    fn clear_entity_override(&mut self, _index: usize) {}
+
+    /// Swaps the effective values of entities `a` and `b`, whose prototypes are `proto_a` and
+    /// `proto_b`.
+    ///
+    /// The default clones both values and writes them back with `set_entity`, so on overlay
+    /// storage both entities end up with an override. It only returns early when `a == b`, or
+    /// when both entities share a prototype and neither has an override.
+    fn swap_entities(&mut self, a: usize, proto_a: usize, b: usize, proto_b: usize)
+    where
+        T: Clone,
+    {
+        if a == b
+            || (proto_a == proto_b
+                && self.override_state(a) == Some(false)
+                && self.override_state(b) == Some(false))
+        {
+            return;
+        }
+        let value_a = self.get_entity_cow(a, proto_a).into_owned();
+        let value_b = self.get_entity_cow(b, proto_b).into_owned();
+        self.set_entity(a, value_b);
+        self.set_entity(b, value_a);
+    }
+
+    /// Appends a copy of entity `src_idx`, including any override it has.
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone;
+
+    /// Swaps prototype rows `a` and `b` (called on the prototypes table).
+    fn swap_prototypes(&mut self, a: usize, b: usize);
+
+    /// Swaps the runtime table's copies of prototypes `a` and `b`, if it keeps any.
+    ///
+    /// No-op for dense storage, whose runtime rows are per-entity, not per-prototype.
+    fn swap_prototype_copies(&mut self, _a: usize, _b: usize) {}
+
+    /// Replaces every prototype row with `f(row)` (called on the prototypes table).
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T);
+
+    /// Applies `f` to the runtime table's copies of the prototypes, if it keeps any.
+    ///
+    /// No-op for dense storage, whose runtime rows are per-entity, not per-prototype.
+    fn transform_prototype_copies(&mut self, _f: &dyn Fn(&T) -> T) {}
+
+    /// Number of entity rows held: the length for dense storage, instances for overlays.
+    /// `None` for storage that holds no rows (`PhantomData`), and by default.
+    fn entity_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// Number of prototype rows held: the length for dense storage, prototypes for overlays.
+    /// `None` for storage that holds no rows (`PhantomData`), and by default.
+    fn prototype_row_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// Allocated row capacity: `Vec::capacity` for dense storage, the capacity of the
+    /// prototype data for overlays (its length when borrowed). 0 for `PhantomData`, and by
+    /// default.
+    fn row_capacity(&self) -> usize {
+        0
+    }
+
+    /// Whether this is overlay storage (prototype rows plus per-instance overrides) rather
+    /// than dense storage with one value per row.
+    fn is_overlay() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Number of per-instance overrides held (always 0 for dense storage).
+    fn override_count(&self) -> usize {
+        0
+    }
+
+    /// Clears the overrides of every entity `i` with `entity_proto_ids[i] == proto_id`,
+    /// returning how many were cleared (always 0 for dense storage).
+    fn clear_overrides_for_prototype(&mut self, _proto_id: usize, _entity_proto_ids: &[usize]) -> usize {
+        0
+    }
+
+    /// Appends already-deserialized prototypes (no serde round trip).
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone;
+
+    /// Serialize the effective value of entity `idx`, whose prototype is `proto_idx`.
+    ///
+    /// Used for type-erased entity export (e.g. an entity clipboard).
+    fn get_as_json(&self, idx: usize, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(self.get_entity(idx, proto_idx)).expect("failed to serialize entity field")
+    }
+
+    /// Serialize the prototype at `proto_idx` (a row of the prototypes table).
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize;
+}
+
+// Implement for standard Vec (Dense storage)
+impl<T> Storage<T> for Vec<T> 
+where T: Clone + Default + DeserializeOwned 
+{
+    fn init_from_prototypes(&mut self, _prototypes: &Self) {
+        // Runtime instance storage starts empty for dense Vec.
+    }
+
+    fn push_json(&mut self, json: &Value) {
+        let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+        self.push(val);
+    }
+
+    fn replace_json(&mut self, proto_idx: usize, json: &Value) {
+        self[proto_idx] = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+    }
+
+    fn push_from_prototype(&mut self, source: &Self, proto_idx: usize) {
+        self.push(source[proto_idx].clone());
+    }
+
+    fn push_default(&mut self) {
+        self.push(T::default());
+    }
+
+    fn swap_remove(&mut self, index: usize) {
+        self.swap_remove(index);
+    }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, _proto_idx: usize) -> &T {
+        &self[index]
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self[index] = value;
+    }
+
+    fn swap_entities(&mut self, a: usize, _proto_a: usize, b: usize, _proto_b: usize)
+    where
+        T: Clone,
+    {
+        self.swap(a, b);
+    }
+
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.push(self[src_idx].clone());
+    }
+
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        self.extend_from_slice(data);
+    }
+
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        self.swap(a, b);
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in self.iter_mut() {
+            *value = f(value);
+        }
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn row_capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&self[proto_idx]).expect("failed to serialize prototype field")
+    }
+}
+
+/// Zero-sized tag fields (`tag: PhantomData<Marker>`) can be registered like any other field.
+/// They hold no data, so loading, spawning and removal are no-ops, and they export as `null`.
+impl<X> Storage<PhantomData<X>> for PhantomData<X> {
+    fn init_from_prototypes(&mut self, _prototypes: &Self) {}
+
+    fn push_json(&mut self, _json: &Value) {}
+
+    fn replace_json(&mut self, _proto_idx: usize, _json: &Value) {}
+
+    fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {}
+
+    fn push_default(&mut self) {}
+
+    fn swap_remove(&mut self, _index: usize) {}
+
+    fn prototype(&self, _proto_idx: usize) -> &PhantomData<X> {
+        &PhantomData
+    }
+
+    fn get_entity(&self, _index: usize, _proto_idx: usize) -> &PhantomData<X> {
+        &PhantomData
+    }
+
+    fn set_entity(&mut self, _index: usize, _value: PhantomData<X>) {}
+
+    fn clone_entity(&mut self, _src_idx: usize) {}
+
+    fn swap_prototypes(&mut self, _a: usize, _b: usize) {}
+
+    fn transform_prototypes(&mut self, _f: &dyn Fn(&PhantomData<X>) -> PhantomData<X>) {}
+
+    fn push_prototypes_from_slice(&mut self, _data: &[PhantomData<X>]) {}
+
+    fn export_json(&self, _proto_idx: usize) -> Value {
+        Value::Null
+    }
+}
+
+// Implement for Overlay (Sparse/COW storage)
+impl<T> Storage<T> for Overlay<T> 
+where T: Clone + Default + DeserializeOwned 
+{
+    fn init_from_prototypes(&mut self, prototypes: &Self) {
+        // Copy prototype/template data; clear per-instance overrides.
+        self.dense_data = prototypes.dense_data.clone();
+        self.global_default = prototypes.global_default.clone();
+        self.sparse_data.clear();
+        #[cfg(feature = "ordered_overrides")]
+        self.override_order.clear();
+        self.presence.clear();
+        self.lazy_marks.clear();
+        self.instance_len = 0;
+    }
+
+    fn push_json(&mut self, json: &Value) {
+        // For an overlay, loading a prototype appends to `dense_data` (the templates).
+        let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+        self.dense_data.push(val);
+    }
+
+    fn replace_json(&mut self, proto_idx: usize, json: &Value) {
+        self.dense_data[proto_idx] = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+    }
+
+    fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {
+        // When spawning a LIVE entity, an Overlay doesn't copy prototype data yet.
+        // It just extends the instance bitmask (copy-on-write on first mutation).
+        self.push_instance();
+    }
+
+    fn push_default(&mut self) {
+        self.push_instance();
+        self.set(self.instance_len - 1, T::default());
+    }
+
+    fn swap_remove(&mut self, index: usize) {
+        self.swap_remove_instance(index);
+    }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T {
+        self.get(index, proto_idx)
+    }
+
+    fn override_state(&self, index: usize) -> Option<bool> {
+        Some(self.has_override(index))
+    }
+
+    fn pin_entity(&mut self, index: usize, proto_idx: usize) {
+        self.get_mut(index, proto_idx);
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self.set(index, value);
+    }
+
+    fn clear_entity_override(&mut self, index: usize) {
+        self.clear_override(index);
+    }
+
+    /// Entities of the same prototype trade overrides (if any); otherwise the two values are
+    /// swapped in place through `get_both_mut`, which only clones a prototype value for an
+    /// entity that has no override yet.
+    fn swap_entities(&mut self, a: usize, proto_a: usize, b: usize, proto_b: usize)
+    where
+        T: Clone,
+    {
+        if a == b {
+            return;
+        }
+        if proto_a == proto_b && !(self.has_override(a) && self.has_override(b)) {
+            let value_a = self.pop_override(a);
+            let value_b = self.pop_override(b);
+            if let Some(value) = value_b {
+                self.set(a, value);
+            }
+            if let Some(value) = value_a {
+                self.set(b, value);
+            }
+            return;
+        }
+        let (value_a, value_b) = self.get_both_mut(a, proto_a, b, proto_b);
+        std::mem::swap(value_a, value_b);
+    }
+
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.clone_instance(src_idx);
+    }
+
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        Overlay::push_prototypes_from_slice(self, data);
+    }
+
+    fn override_count(&self) -> usize {
+        Overlay::override_count(self)
+    }
+
+    fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        Overlay::clear_overrides_for_prototype(self, proto_id, entity_proto_ids)
+    }
+
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn swap_prototype_copies(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        Overlay::transform_prototypes(self, f);
+    }
+
+    fn transform_prototype_copies(&mut self, f: &dyn Fn(&T) -> T) {
+        Overlay::transform_prototypes(self, f);
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.instances_len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.prototypes_len())
+    }
+
+    fn row_capacity(&self) -> usize {
+        self.dense_data.capacity()
+    }
+
+    fn is_overlay() -> bool {
+        true
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&self.dense_data[proto_idx]).expect("failed to serialize prototype field")
+    }
+}
+
+// Honestly its kind of just fat so we can use it in both tables to simplify our shit
+// Can be used for - sparse data , defaults, copy on write data
+/// Prototype data plus copy-on-write per-instance overrides.
+///
+/// Overlay is Send + Sync when T is; no interior mutability is used.
+#[derive(Clone)]
+pub struct Overlay<T> {
+    /// Prototype/template data (indexed by `prototype_id`).
+    pub dense_data: Vec<T>,
+
+    /// Per-instance overrides (keyed by `instance_id`).
+    pub sparse_data: HashMap<usize, T>,
+
+    /// Bitmask for which instances have overrides in `sparse_data`.
+    /// One bit per instance: 1 => present in `sparse_data`, 0 => use prototype fallback.
+    pub presence: Vec<u64>,
+
+    /// Logical number of instances being tracked by this overlay.
+    instance_len: usize,
+
+    /// Keys of `sparse_data` in the order they were first set.
+    #[cfg(feature = "ordered_overrides")]
+    override_order: Vec<usize>,
+
+    /// Fallback for prototype ids with no prototype (see `get_or_global_default`).
+    global_default: Option<T>,
+
+    /// What `mark_for_write` does (see `CowMode`).
+    cow_mode: CowMode,
+
+    /// Instances marked by `mark_for_write` in `CowMode::LazyMark`, not yet committed.
+    lazy_marks: Vec<bool>,
+
+    /// Called with each newly set override (see `with_set_hook`).
+    #[cfg(feature = "hooks")]
+    on_set: Option<OverrideSetHook<T>>,
+
+    /// Called with each cleared override's instance id (see `with_clear_hook`).
+    #[cfg(feature = "hooks")]
+    on_clear: Option<OverrideClearHook>,
+}
+
+/// `Overlay` callback for a set override: `(instance_id, new_value)`.
+#[cfg(feature = "hooks")]
+pub type OverrideSetHook<T> = Arc<dyn Fn(usize, &T) + Send + Sync>;
+
+/// `Overlay` callback for a cleared override: `instance_id`.
+#[cfg(feature = "hooks")]
+pub type OverrideClearHook = Arc<dyn Fn(usize) + Send + Sync>;
+
+/// How `Overlay::mark_for_write` handles the copy-on-write of an unmodified instance.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CowMode {
+    /// Clone the prototype value into an override immediately.
+    #[default]
+    EagerClone,
+    /// Only record the instance as potentially dirty; `commit_lazy_overrides` does the clones.
+    LazyMark,
+}
+
+impl<T> Default for Overlay<T> {
+    fn default() -> Self {
+        Self {
+            dense_data: Vec::new(),
+            sparse_data: HashMap::new(),
+            presence: Vec::new(),
+            instance_len: 0,
+            #[cfg(feature = "ordered_overrides")]
+            override_order: Vec::new(),
+            global_default: None,
+            cow_mode: CowMode::EagerClone,
+            lazy_marks: Vec::new(),
+            #[cfg(feature = "hooks")]
+            on_set: None,
+            #[cfg(feature = "hooks")]
+            on_clear: None,
+        }
+    }
+}
+
+/// Collects prototype values into `dense_data`, with no instances.
+impl<T> FromIterator<T> for Overlay<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            dense_data: iter.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Appends prototype values to `dense_data`.
+impl<T> Extend<T> for Overlay<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.dense_data.extend(iter);
+    }
+}
+
+/// Prototype value by id: `overlay[MyProto::Goblin]` for a `#[repr(usize)]` prototype enum
+/// implementing `Into<usize>`, or a plain `usize`/`PrototypeId`.
+impl<T, E: Into<usize>> Index<E> for Overlay<T> {
+    type Output = T;
+
+    fn index(&self, proto_id: E) -> &T {
+        &self.dense_data[proto_id.into()]
+    }
+}
+
+/// A table's `prototype_id` column as row indices: borrowed when it is already a `Vec<usize>`,
+/// converted otherwise (e.g. a prototype enum column).
+#[doc(hidden)]
+#[allow(clippy::ptr_arg)] // `Vec<P>` rather than `[P]` so it can be downcast through `Any`
+pub fn __prototype_ids_as_usize<P: Copy + Into<usize> + 'static>(ids: &Vec<P>) -> Cow<'_, [usize]> {
+    match (ids as &dyn Any).downcast_ref::<Vec<usize>>() {
+        Some(ids) => Cow::Borrowed(ids),
+        None => Cow::Owned(ids.iter().map(|&id| id.into()).collect()),
+    }
+}
+
+/// Whether the field `field` selects from an `E` is overlay storage; lets
+/// `impl_load_prototype!` ask without an instance of `E`.
+#[doc(hidden)]
+pub fn __storage_is_overlay<E, T, S: Storage<T>>(_field: impl Fn(&E) -> &S) -> bool {
+    S::is_overlay()
+}
+
+/// Serialized form of `Overlay`: the presence bits aren't stored, they're rebuilt from the
+/// override ids when loading.
+#[derive(Serialize)]
+struct OverlayRef<'a, T> {
+    dense_data: &'a [T],
+    instance_len: usize,
+    overrides: Vec<(usize, &'a T)>,
+}
+
+/// Owned counterpart of `OverlayRef`, for deserializing.
+#[derive(Deserialize)]
+struct OverlayOwned<T> {
+    dense_data: Vec<T>,
+    instance_len: usize,
+    overrides: Vec<(usize, T)>,
+}
+
+/// Writes the prototypes, the instance count and the overrides as `(instance_id, value)`
+/// pairs (ascending, or in insertion order with `ordered_overrides`).
+///
+/// The global default, copy-on-write mode, lazy marks and hooks are runtime configuration and
+/// aren't serialized.
+impl<T: Serialize> Serialize for Overlay<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[cfg(feature = "ordered_overrides")]
+        let ids = self.override_order.clone();
+        #[cfg(not(feature = "ordered_overrides"))]
+        let ids = {
+            let mut ids: Vec<usize> = self.sparse_data.keys().copied().collect();
+            ids.sort_unstable();
+            ids
+        };
+        OverlayRef {
+            dense_data: &self.dense_data,
+            instance_len: self.instance_len,
+            overrides: ids.into_iter().map(|id| (id, &self.sparse_data[&id])).collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Reads the format written by `Serialize`, rebuilding the presence bits from the override ids.
+/// Fails on an override id past the instance count or listed twice.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Overlay<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let owned = OverlayOwned::<T>::deserialize(deserializer)?;
+        let mut overlay = Overlay {
+            dense_data: owned.dense_data,
+            presence: vec![0; owned.instance_len.div_ceil(64)],
+            instance_len: owned.instance_len,
+            ..Overlay::default()
+        };
+        for (instance_id, value) in owned.overrides {
+            if instance_id >= overlay.instance_len {
+                return Err(D::Error::custom(format_args!(
+                    "Overlay override for instance {instance_id} past instance_len {}",
+                    overlay.instance_len
+                )));
+            }
+            if overlay.sparse_data.insert(instance_id, value).is_some() {
+                return Err(D::Error::custom(format_args!(
+                    "Overlay override for instance {instance_id} listed twice"
+                )));
+            }
+            #[cfg(feature = "ordered_overrides")]
+            overlay.override_order.push(instance_id);
+            overlay.presence[instance_id >> 6] |= 1u64 << (instance_id & 63);
+        }
+        Ok(overlay)
+    }
+}
+
+impl<T> Overlay<T>
+where
+    T: Clone,
+{
+    #[inline]
+    fn word_bit(instance_id: usize) -> (usize, u64) {
+        let word = instance_id >> 6;
+        let bit = instance_id & 63;
+        (word, 1u64 << bit)
+    }
+
+    #[inline]
+    fn ensure_presence_capacity(&mut self, instance_id: usize) {
+        let (word, _) = Self::word_bit(instance_id);
+        if self.presence.len() <= word {
+            self.presence.resize(word + 1, 0);
+        }
+    }
+
+    /// Records a new `sparse_data` key; call before inserting it.
+    #[cfg(feature = "ordered_overrides")]
+    fn track_insert(&mut self, instance_id: usize) {
+        if !self.sparse_data.contains_key(&instance_id) {
+            self.override_order.push(instance_id);
+        }
+    }
+
+    /// Forgets a removed `sparse_data` key, keeping the order of the rest.
+    #[cfg(feature = "ordered_overrides")]
+    fn track_remove(&mut self, instance_id: usize) {
+        if let Some(pos) = self.override_order.iter().position(|&id| id == instance_id) {
+            self.override_order.remove(pos);
+        }
+    }
+
+    /// Number of spawned instances represented by this overlay.
+    pub fn instances_len(&self) -> usize {
+        self.instance_len
+    }
+
+    /// Number of loaded prototypes/templates represented by this overlay.
+    pub fn prototypes_len(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    /// Replaces every prototype value with `f(value)` (e.g. a global balance modifier).
+    ///
+    /// Instances without an override see the new values on their next `get`; overridden
+    /// instances keep their own values.
+    pub fn transform_prototypes(&mut self, f: impl Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    /// A new overlay with no instances whose prototypes are this overlay's followed by
+    /// `other`'s, e.g. base game prototypes plus the ones a mod adds. `other`'s prototype `i`
+    /// becomes prototype `self.prototypes_len() + i`.
+    pub fn merge_prototype_tables(&self, other: &Overlay<T>) -> Overlay<T> {
+        self.dense_data.iter().chain(&other.dense_data).cloned().collect()
+    }
+
+    /// Appends clones of `other`'s prototypes `src_range` (e.g. merging part of another
+    /// prototype file). `other`'s prototype `src_range.start + k` becomes prototype
+    /// `self.prototypes_len() + k`, counting from before the call.
+    pub fn copy_prototype_range(&mut self, other: &Overlay<T>, src_range: std::ops::Range<usize>) {
+        if src_range.end > other.dense_data.len() {
+            panic!(
+                "Overlay copy_prototype_range out of bounds: {}..{} of {}",
+                src_range.start,
+                src_range.end,
+                other.dense_data.len()
+            );
+        }
+        self.dense_data.extend_from_slice(&other.dense_data[src_range]);
+    }
+
+    /// Replaces prototype `proto_ids[k]` with `patch`'s prototype `k`, e.g. a mod overriding
+    /// specific base prototypes. Instances and their overrides are untouched.
+    pub fn apply_prototype_patch(&mut self, patch: &Overlay<T>, proto_ids: &[usize]) {
+        if patch.dense_data.len() != proto_ids.len() {
+            panic!(
+                "Overlay apply_prototype_patch: {} patch prototypes for {} ids",
+                patch.dense_data.len(),
+                proto_ids.len()
+            );
+        }
+        for (&proto_id, value) in proto_ids.iter().zip(&patch.dense_data) {
+            self.dense_data[proto_id] = value.clone();
+        }
+    }
+
+    /// Grows (filling with `default_value`) or shrinks the prototype data to `new_len`, e.g.
+    /// when a difficulty setting changes the number of enemy types.
+    ///
+    /// `proto_ids[i]` is instance `i`'s prototype. Panics when shrinking would leave an
+    /// instance pointing past the end.
+    pub fn resize_prototypes(&mut self, new_len: usize, default_value: T, proto_ids: &[usize]) {
+        if new_len < self.dense_data.len() {
+            let dangling = proto_ids[..self.instance_len]
+                .iter()
+                .filter(|&&p| p >= new_len)
+                .count();
+            if dangling > 0 {
+                panic!("Overlay resize_prototypes to {new_len}: {dangling} instance(s) use a removed prototype");
+            }
+        }
+        self.dense_data.resize(new_len, default_value);
+    }
+
+    /// Exchanges `instance_id`'s effective value with prototype `proto_id`'s value (e.g.
+    /// recording a procedurally tuned instance as the new prototype). The instance keeps the
+    /// old prototype value as an override, or no override if the two values were equal.
+    ///
+    /// Other instances of `proto_id` without an override see the new prototype value.
+    pub fn swap_with_prototype(&mut self, instance_id: usize, proto_id: usize)
+    where
+        T: PartialEq,
+    {
+        let instance_value = self.get(instance_id, proto_id).clone();
+        let old_prototype = std::mem::replace(&mut self.dense_data[proto_id], instance_value);
+        if old_prototype == self.dense_data[proto_id] {
+            self.clear_override(instance_id);
+        } else {
+            self.set(instance_id, old_prototype);
+        }
+    }
+
+    /// Drops every prototype, keeping instances and their overrides (e.g. before reloading
+    /// scripted defaults).
+    ///
+    /// Until prototypes are loaded again, `get` on an instance without an override panics
+    /// (there is no prototype to fall back to); overridden instances still read their own
+    /// values. The caller is expected to reload the prototype data right away and re-point
+    /// instances' prototype ids as needed.
+    pub fn clear_prototype_data(&mut self) {
+        self.dense_data.clear();
+    }
+
+    /// Removes and returns the last prototype (e.g. discarding one that was just built), or
+    /// `None` if there are none.
+    ///
+    /// `proto_ids[i]` is instance `i`'s prototype. Panics if any instance still uses the last
+    /// prototype; see `remove_last_prototype_if_unused` for the non-panicking version.
+    pub fn take_prototype(&mut self, proto_ids: &[usize]) -> Option<T> {
+        let last = self.dense_data.len().checked_sub(1)?;
+        if proto_ids[..self.instance_len].contains(&last) {
+            panic!("Overlay take_prototype: prototype {last} is still used by an instance");
+        }
+        self.dense_data.pop()
+    }
+
+    /// Like `take_prototype`, but returns `None` (removing nothing) if any instance still uses
+    /// the last prototype.
+    pub fn remove_last_prototype_if_unused(&mut self, proto_ids: &[usize]) -> Option<T> {
+        let last = self.dense_data.len().checked_sub(1)?;
+        if proto_ids[..self.instance_len].contains(&last) {
+            return None;
+        }
+        self.dense_data.pop()
+    }
+
+    /// Appends prototypes that are already in memory, skipping per-entry deserialization.
+    ///
+    /// For `T: Copy` this is a `memcpy`.
+    pub fn push_prototypes_from_slice(&mut self, data: &[T]) {
+        self.dense_data.extend_from_slice(data);
+    }
+
+    /// Adds a new instance slot (no override set).
+    pub fn push_instance(&mut self) {
+        let id = self.instance_len;
+        self.instance_len += 1;
+        self.ensure_presence_capacity(id);
+        // bit defaults to 0 => no override
+    }
+
+    /// Returns true if this instance has an override.
+    pub fn has_override(&self, instance_id: impl Into<InstanceId>) -> bool {
+        let InstanceId(instance_id) = instance_id.into();
+        if instance_id >= self.instance_len {
+            return false;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        (self.presence.get(word).copied().unwrap_or(0) & mask) != 0
+    }
+
+    /// Number of instances with an override.
+    pub fn override_count(&self) -> usize {
+        self.sparse_data.len()
+    }
+
+    /// Appends a new instance that copies `src_idx`'s override (if any), returning its id.
+    pub fn clone_instance(&mut self, src_idx: usize) -> usize {
+        let id = self.instance_len;
+        self.push_instance();
+        if self.has_override(src_idx) {
+            let value = self.sparse_data[&src_idx].clone();
+            self.set(id, value);
+        }
+        id
+    }
+
+    /// Rebuilds `sparse_data` at its current size, releasing the buckets left behind by
+    /// cleared overrides (`HashMap::remove` never shrinks). The `shrink_to_fit` of overlays.
+    pub fn defrag_sparse_data(&mut self) {
+        let old = std::mem::take(&mut self.sparse_data);
+        self.sparse_data = HashMap::with_capacity(old.len());
+        self.sparse_data.extend(old);
+    }
+
+    /// Makes existing instance `dst_id` match `src_id`'s override: a clone of it if `src_id`
+    /// has one (replacing any override `dst_id` had), otherwise no override.
+    ///
+    /// Unlike `clone_instance`, no new instance slot is allocated.
+    pub fn copy_override(&mut self, src_id: usize, dst_id: usize) {
+        if self.has_override(src_id) {
+            let value = self.sparse_data[&src_id].clone();
+            self.set(dst_id, value);
+        } else {
+            self.clear_override(dst_id);
+        }
+    }
+
+    /// Clears an override for `instance_id`, if present.
+    pub fn clear_override(&mut self, instance_id: impl Into<InstanceId>) {
+        let InstanceId(instance_id) = instance_id.into();
+        if instance_id >= self.instance_len {
+            return;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        if let Some(w) = self.presence.get_mut(word) {
+            *w &= !mask;
+        }
+        #[cfg(feature = "ordered_overrides")]
+        if self.sparse_data.contains_key(&instance_id) {
+            self.track_remove(instance_id);
+        }
+        #[cfg(feature = "hooks")]
+        if self.sparse_data.contains_key(&instance_id)
+            && let Some(f) = &self.on_clear
+        {
+            f(instance_id);
+        }
+        self.sparse_data.remove(&instance_id);
+    }
+
+    /// Like `clear_override`, but returns the removed value (e.g. for an undo buffer or to
+    /// move it to another instance) instead of dropping it.
+    pub fn pop_override(&mut self, instance_id: impl Into<InstanceId>) -> Option<T> {
+        let InstanceId(instance_id) = instance_id.into();
+        if !self.has_override(instance_id) {
+            return None;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word] &= !mask;
+        #[cfg(feature = "ordered_overrides")]
+        self.track_remove(instance_id);
+        #[cfg(feature = "hooks")]
+        if let Some(f) = &self.on_clear {
+            f(instance_id);
+        }
+        self.sparse_data.remove(&instance_id)
+    }
+
+    /// Read with fallback to prototype/template data.
+    ///
+    /// Requires `prototype_id` to be known by the caller (stored as a normal field on the SoA).
+    /// The ids are `InstanceId`/`PrototypeId`, so swapping them is a type error; plain `usize`s
+    /// convert into either.
+    pub fn get(&self, instance_id: impl Into<InstanceId>, prototype_id: impl Into<PrototypeId>) -> &T { // maybe we can grab prototype id with a macro
+        let (InstanceId(instance_id), PrototypeId(prototype_id)) = (instance_id.into(), prototype_id.into());
+        if self.has_override(instance_id) {
+            return self
+                .sparse_data
+                .get(&instance_id)
+                .expect("Overlay presence bit set but sparse_data missing entry");
+        }
+        &self.dense_data[prototype_id]
+    }
+
+    /// Like `get`, but returns `global_default` instead of panicking when `prototype_id` has no
+    /// prototype (e.g. a "null prototype" `usize::MAX`).
+    pub fn get_with_fallback<'a>(&'a self, instance_id: usize, prototype_id: usize, global_default: &'a T) -> &'a T {
+        if self.has_override(instance_id) {
+            return self
+                .sparse_data
+                .get(&instance_id)
+                .expect("Overlay presence bit set but sparse_data missing entry");
+        }
+        self.dense_data.get(prototype_id).unwrap_or(global_default)
+    }
+
+    /// Stores a fallback value used by `get_or_global_default`.
+    pub fn set_global_default(&mut self, value: T) {
+        self.global_default = Some(value);
+    }
+
+    /// The fallback set by `set_global_default`, if any.
+    pub fn global_default(&self) -> Option<&T> {
+        self.global_default.as_ref()
+    }
+
+    /// Like `get_with_fallback`, using the stored `global_default`.
+    ///
+    /// Panics if `prototype_id` has no prototype and no global default was set.
+    pub fn get_or_global_default(&self, instance_id: usize, prototype_id: usize) -> &T {
+        match &self.global_default {
+            Some(global_default) => self.get_with_fallback(instance_id, prototype_id, global_default),
+            None => self.get(instance_id, prototype_id),
+        }
+    }
+
+    /// Like `get`, but falls back to `source` instead of this overlay's `dense_data`.
+    pub fn get_with_source<'s, S>(&'s self, instance_id: usize, prototype_id: usize, source: &'s S) -> &'s T
+    where
+        S: PrototypeSource<T> + ?Sized,
+    {
+        if self.has_override(instance_id) {
+            return self
+                .sparse_data
+                .get(&instance_id)
+                .expect("Overlay presence bit set but sparse_data missing entry");
+        }
+        source.get_prototype(prototype_id)
+    }
+
+    /// Write access with copy-on-write from the prototype/template.
+    pub fn get_mut(&mut self, instance_id: impl Into<InstanceId>, prototype_id: impl Into<PrototypeId>) -> &mut T {
+        let (InstanceId(instance_id), PrototypeId(prototype_id)) = (instance_id.into(), prototype_id.into());
+        if instance_id >= self.instance_len {
+            panic!("Overlay get_mut out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+
+        if !self.has_override(instance_id) {
+            let base = self.dense_data[prototype_id].clone();
+            #[cfg(feature = "hooks")]
+            if let Some(f) = &self.on_set {
+                f(instance_id, &base);
+            }
+            #[cfg(feature = "ordered_overrides")]
+            self.track_insert(instance_id);
+            self.sparse_data.insert(instance_id, base);
+            let (word, mask) = Self::word_bit(instance_id);
+            self.ensure_presence_capacity(instance_id);
+            self.presence[word] |= mask;
+        }
+
+        self.sparse_data
+            .get_mut(&instance_id)
+            .expect("Overlay write: sparse_data missing entry after insert")
+    }
+
+    /// Calls `f(instance_id, &value)` whenever an override is set: `set`, `set_many`,
+    /// `set_range`, and the copy-on-write of `get_mut` (with the cloned prototype value).
+    ///
+    /// Meant for invalidation (dirty render passes, physics). Replaces any previous set hook.
+    #[cfg(feature = "hooks")]
+    pub fn with_set_hook(mut self, f: impl Fn(usize, &T) + Send + Sync + 'static) -> Self {
+        self.on_set = Some(Arc::new(f));
+        self
+    }
+
+    /// Calls `f(instance_id)` whenever `clear_override` removes an override (including the
+    /// removed instance's override in `swap_remove_instance`). Replaces any previous clear hook.
+    #[cfg(feature = "hooks")]
+    pub fn with_clear_hook(mut self, f: impl Fn(usize) + Send + Sync + 'static) -> Self {
+        self.on_clear = Some(Arc::new(f));
+        self
+    }
+
+    /// An empty overlay whose `mark_for_write` follows `mode`.
+    pub fn with_cow_mode(mode: CowMode) -> Self {
+        Self {
+            cow_mode: mode,
+            ..Self::default()
+        }
+    }
+
+    /// An empty overlay with room for `n` prototypes, e.g. from a prototype file's header.
+    /// Instance storage is left unallocated, like `Vec::with_capacity` for the prototype table.
+    pub fn with_prototype_capacity(n: usize) -> Self {
+        Self {
+            dense_data: Vec::with_capacity(n),
+            ..Self::default()
+        }
+    }
+
+    /// The copy-on-write mode this overlay was constructed with.
+    pub fn cow_mode(&self) -> CowMode {
+        self.cow_mode
+    }
+
+    /// Declares that `instance_id` is about to be written, without needing the value yet.
+    ///
+    /// In `CowMode::EagerClone` this is the copy-on-write of `get_mut`. In `CowMode::LazyMark`
+    /// it only marks the instance, so it can still be inspected (`get` keeps returning the
+    /// prototype value) until `commit_lazy_overrides` does the clone. `get_mut` itself always
+    /// clones in both modes: handing out `&mut` to the shared prototype would change every
+    /// instance of that prototype.
+    pub fn mark_for_write(&mut self, instance_id: usize, prototype_id: usize) {
+        match self.cow_mode {
+            CowMode::EagerClone => {
+                self.get_mut(instance_id, prototype_id);
+            }
+            CowMode::LazyMark => {
+                if instance_id >= self.instance_len {
+                    panic!("Overlay mark_for_write out of bounds: {instance_id} >= {}", self.instance_len);
+                }
+                if self.lazy_marks.len() <= instance_id {
+                    self.lazy_marks.resize(instance_id + 1, false);
+                }
+                self.lazy_marks[instance_id] = true;
+            }
+        }
+    }
+
+    /// Whether `instance_id` is marked by `mark_for_write` and not yet committed.
+    pub fn is_marked_for_write(&self, instance_id: usize) -> bool {
+        self.lazy_marks.get(instance_id).copied().unwrap_or(false)
+    }
+
+    /// Clones the prototype value into an override for every instance marked by
+    /// `mark_for_write` (instances that already have one keep it), then clears the marks.
+    ///
+    /// `proto_ids[i]` is instance `i`'s prototype. Returns how many overrides were created.
+    pub fn commit_lazy_overrides(&mut self, proto_ids: &[usize]) -> usize {
+        let marks = std::mem::take(&mut self.lazy_marks);
+        let mut committed = 0;
+        for (instance_id, _) in marks.iter().enumerate().filter(|&(_, &marked)| marked) {
+            if !self.has_override(instance_id) {
+                self.set(instance_id, self.dense_data[proto_ids[instance_id]].clone());
+                committed += 1;
+            }
+        }
+        committed
+    }
+
+    /// Applies a JSON merge patch (see `json_merge_patch`) to `instance_id`'s effective value
+    /// and stores the result as its override, e.g. a component delta from the network.
+    /// A `null` patch clears the override instead.
+    ///
+    /// On error (the patched JSON no longer deserializes as `T`) nothing is changed.
+    pub fn apply_json_patch(&mut self, instance_id: usize, proto_id: usize, patch: &Value) -> Result<(), serde_json::Error>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        if patch.is_null() {
+            self.clear_override(instance_id);
+            return Ok(());
+        }
+        let mut value = serde_json::to_value(self.get(instance_id, proto_id))?;
+        json_merge_patch(&mut value, patch);
+        self.set(instance_id, serde_json::from_value(value)?);
+        Ok(())
+    }
+
+    /// Sets `new_val` as `instance_id`'s override only if `pred` holds for its current
+    /// effective value (e.g. only deal damage while HP > 0). Returns whether it was set.
+    pub fn conditional_set(
+        &mut self,
+        instance_id: usize,
+        proto_id: usize,
+        pred: impl Fn(&T) -> bool,
+        new_val: T,
+    ) -> bool {
+        if !pred(self.get(instance_id, proto_id)) {
+            return false;
+        }
+        self.set(instance_id, new_val);
+        true
+    }
+
+    /// Like `get_mut`, but the copy-on-write stores `f(prototype value)` instead of a plain
+    /// clone. An existing override is returned as is, without calling `f`
+    /// (cf. `HashMap::entry().or_insert_with()`).
+    pub fn get_or_insert_with(
+        &mut self,
+        instance_id: usize,
+        prototype_id: usize,
+        f: impl FnOnce(&T) -> T,
+    ) -> &mut T {
+        if !self.has_override(instance_id) {
+            let value = f(&self.dense_data[prototype_id]);
+            self.set(instance_id, value);
+        }
+        self.sparse_data
+            .get_mut(&instance_id)
+            .expect("Overlay write: sparse_data missing entry after insert")
+    }
+
+    /// Like `get_mut`, but the copy-on-write clone only happens if the returned handle is
+    /// actually mutated (`DerefMut`); reading through it (`Deref`) never clones.
+    pub fn get_lazy_mut(&mut self, instance_id: usize, prototype_id: usize) -> LazyMut<'_, T> {
+        if instance_id >= self.instance_len {
+            panic!("Overlay get_lazy_mut out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        LazyMut {
+            overlay: self,
+            instance_id,
+            prototype_id,
+        }
+    }
+
+    /// Write access to two different instances at once (e.g. to swap or transfer values), with
+    /// the same copy-on-write from their prototypes as `get_mut`.
+    ///
+    /// Panics if `a == b`.
+    pub fn get_both_mut(
+        &mut self,
+        a: impl Into<InstanceId>,
+        proto_a: impl Into<PrototypeId>,
+        b: impl Into<InstanceId>,
+        proto_b: impl Into<PrototypeId>,
+    ) -> (&mut T, &mut T) {
+        let (InstanceId(a), InstanceId(b)) = (a.into(), b.into());
+        assert_ne!(a, b, "Overlay get_both_mut: both ids are {a}");
+        self.get_mut(a, proto_a);
+        self.get_mut(b, proto_b);
+        let [Some(value_a), Some(value_b)] = self.sparse_data.get_disjoint_mut([&a, &b]) else {
+            unreachable!("get_mut inserted both overrides");
+        };
+        (value_a, value_b)
+    }
+
+    /// Sets an override value for `instance_id` (marks presence bit).
+    pub fn set(&mut self, instance_id: impl Into<InstanceId>, value: T) {
+        let InstanceId(instance_id) = instance_id.into();
+        if instance_id >= self.instance_len {
+            panic!("Overlay set out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        #[cfg(feature = "hooks")]
+        if let Some(f) = &self.on_set {
+            f(instance_id, &value);
+        }
+        #[cfg(feature = "ordered_overrides")]
+        self.track_insert(instance_id);
+        self.sparse_data.insert(instance_id, value);
+        let (word, mask) = Self::word_bit(instance_id);
+        self.ensure_presence_capacity(instance_id);
+        self.presence[word] |= mask;
+    }
+
+    /// Clears the override of every instance `i` with `entity_proto_ids[i] == proto_id`
+    /// (e.g. a prototype reset event), returning how many were cleared.
+    ///
+    /// Only instances with a presence bit set are checked, so words without overrides are
+    /// skipped entirely.
+    pub fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        let mut cleared = 0;
+        for word in 0..self.presence.len() {
+            let mut bits = self.presence[word];
+            while bits != 0 {
+                let instance_id = (word << 6) | bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                if entity_proto_ids[instance_id] == proto_id {
+                    self.clear_override(instance_id);
+                    cleared += 1;
+                }
+            }
+        }
+        cleared
+    }
+
+    /// Sets the same override value for every instance in `instance_ids`.
+    ///
+    /// Cheaper than calling `set` in a loop: `sparse_data` is reserved once and presence bits
+    /// are OR'd a word at a time.
+    pub fn set_many(&mut self, instance_ids: &[usize], value: T) {
+        let Some(&max_id) = instance_ids.iter().max() else {
+            return;
+        };
+        if max_id >= self.instance_len {
+            panic!("Overlay set_many out of bounds: {max_id} >= {}", self.instance_len);
+        }
+        self.ensure_presence_capacity(max_id);
+        self.sparse_data.reserve(instance_ids.len());
+
+        let mut pending_word = usize::MAX;
+        let mut pending_mask = 0u64;
+        for &instance_id in instance_ids {
+            #[cfg(feature = "hooks")]
+            if let Some(f) = &self.on_set {
+                f(instance_id, &value);
+            }
+            #[cfg(feature = "ordered_overrides")]
+            self.track_insert(instance_id);
+            self.sparse_data.insert(instance_id, value.clone());
+            let (word, mask) = Self::word_bit(instance_id);
+            if word != pending_word {
+                if pending_mask != 0 {
+                    self.presence[pending_word] |= pending_mask;
+                }
+                pending_word = word;
+                pending_mask = 0;
+            }
+            pending_mask |= mask;
+        }
+        self.presence[pending_word] |= pending_mask;
+    }
+
+    /// Sets the same override value for every instance in `start..end`.
+    pub fn set_range(&mut self, start: usize, end: usize, value: T) {
+        if start >= end {
+            return;
+        }
+        if end > self.instance_len {
+            panic!("Overlay set_range out of bounds: {end} > {}", self.instance_len);
+        }
+        self.ensure_presence_capacity(end - 1);
+        self.sparse_data.reserve(end - start);
+        for instance_id in start..end {
+            #[cfg(feature = "hooks")]
+            if let Some(f) = &self.on_set {
+                f(instance_id, &value);
+            }
+            #[cfg(feature = "ordered_overrides")]
+            self.track_insert(instance_id);
+            self.sparse_data.insert(instance_id, value.clone());
+        }
+
+        let (first_word, _) = Self::word_bit(start);
+        let (last_word, _) = Self::word_bit(end - 1);
+        for word in first_word..=last_word {
+            let lo = if word == first_word { start & 63 } else { 0 };
+            let hi = if word == last_word { ((end - 1) & 63) + 1 } else { 64 };
+            let mask = if hi - lo == 64 { u64::MAX } else { ((1u64 << (hi - lo)) - 1) << lo };
+            self.presence[word] |= mask;
+        }
+    }
+
+    /// Sets many overrides at once (e.g. a network packet of component deltas). Later entries
+    /// win for duplicate instance ids.
+    ///
+    /// The updates are collected and bounds-checked before anything is changed, so an
+    /// out-of-bounds id panics with the overlay untouched. The HashMap inserts and the presence
+    /// bits are then done in separate passes: ids are sorted so each presence word is OR-ed once.
+    pub fn bulk_set_from_iter(&mut self, updates: impl IntoIterator<Item = (usize, T)>) {
+        let updates: Vec<(usize, T)> = updates.into_iter().collect();
+        let Some(max_id) = updates.iter().map(|&(id, _)| id).max() else {
+            return;
+        };
+        if max_id >= self.instance_len {
+            panic!("Overlay bulk_set_from_iter out of bounds: {max_id} >= {}", self.instance_len);
+        }
+        self.ensure_presence_capacity(max_id);
+        self.sparse_data.reserve(updates.len());
+        let mut ids = Vec::with_capacity(updates.len());
+        for (instance_id, value) in updates {
+            #[cfg(feature = "hooks")]
+            if let Some(f) = &self.on_set {
+                f(instance_id, &value);
+            }
+            #[cfg(feature = "ordered_overrides")]
+            self.track_insert(instance_id);
+            self.sparse_data.insert(instance_id, value);
+            ids.push(instance_id);
+        }
+
+        ids.sort_unstable();
+        for chunk in ids.chunk_by(|a, b| a >> 6 == b >> 6) {
+            let (word, _) = Self::word_bit(chunk[0]);
+            self.presence[word] |= chunk.iter().fold(0, |mask, &id| mask | Self::word_bit(id).1);
+        }
+    }
+
+    /// Calls `f(instance_id, effective value)` for instances `0..entity_count` and sets the
+    /// override to whatever `f` returns as `Some`, skipping the insert for unchanged
+    /// instances. With `clear_on_none`, a `None` also clears the instance's override, so one
+    /// pass can both override and reset. Returns the number of overrides set.
+    ///
+    /// `proto_ids[i]` is instance `i`'s prototype.
+    pub fn update_filtered(
+        &mut self,
+        entity_count: usize,
+        proto_ids: &[usize],
+        clear_on_none: bool,
+        f: impl Fn(usize, &T) -> Option<T>,
+    ) -> usize {
+        let mut updated = 0;
+        for (instance_id, &proto_id) in proto_ids[..entity_count].iter().enumerate() {
+            match f(instance_id, self.get(instance_id, proto_id)) {
+                Some(value) => {
+                    self.set(instance_id, value);
+                    updated += 1;
+                }
+                None if clear_on_none => {
+                    self.clear_override(instance_id);
+                }
+                None => {}
+            }
+        }
+        updated
+    }
+
+    /// Swap-remove an instance slot, keeping O(1) semantics consistent with `Vec::swap_remove`.
+    ///
+    /// If the last instance had an override, it is moved into `index`.
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        if index >= self.instance_len {
+            panic!(
+                "Overlay swap_remove out of bounds: {index} >= {}",
+                self.instance_len
+            );
+        }
+
+        let last = self.instance_len - 1;
+
+        // Remove index override (if any).
+        self.clear_override(index);
+
+        if index != last {
+            let last_has = self.has_override(last);
+
+            if last_has {
+                if let Some(v) = self.sparse_data.remove(&last) {
+                    self.sparse_data.insert(index, v);
+                    #[cfg(feature = "ordered_overrides")]
+                    if let Some(id) = self.override_order.iter_mut().find(|id| **id == last) {
+                        *id = index;
+                    }
+                } else {
+                    panic!("Overlay presence bit set for last but sparse_data missing entry");
+                }
+            }
+
+            // Copy last presence bit to index.
+            let (iw, im) = Self::word_bit(index);
+            let (lw, lm) = Self::word_bit(last);
+            self.ensure_presence_capacity(index);
+            self.ensure_presence_capacity(last);
+
+            if last_has {
+                self.presence[iw] |= im;
+            } else {
+                self.presence[iw] &= !im;
+            }
+
+            // Clear last bit.
+            self.presence[lw] &= !lm;
+        } else {
+            // Clear last bit (already cleared by clear_override(index)).
+            let (lw, lm) = Self::word_bit(last);
+            if let Some(w) = self.presence.get_mut(lw) {
+                *w &= !lm;
+            }
+        }
+
+        // Move last's lazy mark to index; marks past the end of `lazy_marks` are unset.
+        if index < self.lazy_marks.len() {
+            self.lazy_marks[index] = self.lazy_marks.get(last).copied().unwrap_or(false);
+            self.lazy_marks.truncate(last);
+        }
+
+        self.instance_len -= 1;
+
+        #[cfg(debug_assertions)]
+        self.assert_no_dangling_overrides();
+    }
+
+    /// Removes the instances in `range` (e.g. a batch despawn at the end of a wave), yielding
+    /// each one's override. Later instances shift down to close the gap, keeping their order,
+    /// so instance `range.end + k` becomes `range.start + k`.
+    ///
+    /// O(overrides + instances): every override and presence bit after the range is moved.
+    /// For removing single instances where order doesn't matter, `swap_remove_instance` is
+    /// O(1).
+    pub fn drain_instances(&mut self, range: std::ops::Range<usize>) -> DrainInstances<'_, T> {
+        let std::ops::Range { start, end } = range;
+        if start > end || end > self.instance_len {
+            panic!("Overlay drain_instances out of bounds: {start}..{end} of {}", self.instance_len);
+        }
+        let removed = end - start;
+
+        let mut drained: Vec<(usize, Option<T>)> = (start..end).map(|id| (id, None)).collect();
+        for (instance_id, value) in std::mem::take(&mut self.sparse_data) {
+            if instance_id < start {
+                self.sparse_data.insert(instance_id, value);
+            } else if instance_id < end {
+                drained[instance_id - start].1 = Some(value);
+            } else {
+                self.sparse_data.insert(instance_id - removed, value);
+            }
+        }
+
+        #[cfg(feature = "hooks")]
+        if let Some(f) = &self.on_clear {
+            for (instance_id, _) in drained.iter().filter(|(_, value)| value.is_some()) {
+                f(*instance_id);
+            }
+        }
+
+        #[cfg(feature = "ordered_overrides")]
+        {
+            self.override_order.retain(|id| !(start..end).contains(id));
+            for id in &mut self.override_order {
+                if *id >= end {
+                    *id -= removed;
+                }
+            }
+        }
+
+        if start < self.lazy_marks.len() {
+            self.lazy_marks.drain(start..end.min(self.lazy_marks.len()));
+        }
+
+        self.instance_len -= removed;
+        self.presence = vec![0; self.instance_len.div_ceil(64)];
+        for &instance_id in self.sparse_data.keys() {
+            let (word, mask) = Self::word_bit(instance_id);
+            self.presence[word] |= mask;
+        }
+
+        DrainInstances {
+            drained: drained.into_iter(),
+            _overlay: PhantomData,
+        }
+    }
+
+    /// `(instance_count, override_count)`, e.g. to decide whether a dense or sparse backend
+    /// suits this field better.
+    pub fn instance_to_sparse_ratio(&self) -> (usize, usize) {
+        (self.instance_len, self.sparse_data.len())
+    }
+
+    /// Lengths and capacities of every internal buffer, for tools that pick storage backends.
+    pub fn storage_stats(&self) -> OverlayStats {
+        OverlayStats {
+            prototype_count: self.dense_data.len(),
+            instance_count: self.instance_len,
+            override_count: self.sparse_data.len(),
+            dense_capacity: self.dense_data.capacity(),
+            sparse_capacity: self.sparse_data.capacity(),
+            presence_words: self.presence.len(),
+        }
+    }
+
+    /// Copies the current overrides, to diff against later with `iter_changed_vs_snapshot`.
+    pub fn snapshot(&self) -> OverlaySnapshot<T> {
+        OverlaySnapshot {
+            presence: self.presence.clone(),
+            sparse_data: self.sparse_data.clone(),
+        }
+    }
+
+    /// Instance ids whose override changed since `snapshot` (e.g. over one simulation frame):
+    /// set, cleared, or set to a different value. Ascending.
+    ///
+    /// XOR-ing the presence words finds set/cleared overrides and skips words with no
+    /// overrides on either side; only overrides present in both are compared by value.
+    /// Assumes no instances were added/removed in between; ids past the current instance
+    /// count aren't reported.
+    pub fn iter_changed_vs_snapshot<'a>(
+        &'a self,
+        snapshot: &'a OverlaySnapshot<T>,
+    ) -> impl Iterator<Item = usize> + 'a
+    where
+        T: PartialEq,
+    {
+        let words = self.presence.len().max(snapshot.presence.len());
+        (0..words).flat_map(move |word| {
+            let now = self.presence.get(word).copied().unwrap_or(0);
+            let then = snapshot.presence.get(word).copied().unwrap_or(0);
+            let mut changed = now ^ then;
+            let mut both = now & then;
+            while both != 0 {
+                let bit = both.trailing_zeros();
+                both &= both - 1;
+                let instance_id = (word << 6) | bit as usize;
+                if self.sparse_data.get(&instance_id) != snapshot.sparse_data.get(&instance_id) {
+                    changed |= 1 << bit;
+                }
+            }
+            std::iter::from_fn(move || {
+                if changed == 0 {
+                    return None;
+                }
+                let bit = changed.trailing_zeros() as usize;
+                changed &= changed - 1;
+                Some((word << 6) | bit)
+            })
+            .filter(move |&instance_id| instance_id < self.instance_len)
+        })
+    }
+
+    /// Iterates the effective value of every instance, where `proto_ids[i]` is instance `i`'s
+    /// prototype (usually the SoA's `prototype_id` field).
+    pub fn iter_all<'a>(&'a self, proto_ids: &'a [usize]) -> impl Iterator<Item = &'a T> + 'a {
+        (0..self.instance_len).map(move |i| self.get(i, proto_ids[i]))
+    }
+
+    /// Fraction of instances that have an override (0.0 when there are no instances).
+    pub fn sparsity(&self) -> f64 {
+        if self.instance_len == 0 {
+            return 0.0;
+        }
+        self.sparse_data.len() as f64 / self.instance_len as f64
+    }
+
+    /// Clones the effective value of every instance into a dense `Vec`.
+    pub fn to_dense_vec(&self, proto_ids: &[usize]) -> Vec<T> {
+        self.iter_all(proto_ids).cloned().collect()
+    }
+
+    /// Converts into a dense per-instance `Vec`, moving overrides rather than cloning them.
+    pub fn upgrade_to_fully_dense(mut self, proto_ids: &[usize]) -> Vec<T> {
+        (0..self.instance_len)
+            .map(|i| match self.sparse_data.remove(&i) {
+                Some(value) => value,
+                None => self.dense_data[proto_ids[i]].clone(),
+            })
+            .collect()
+    }
+
+    /// Escape hatch for overlays where nearly every instance ends up overridden: if
+    /// `sparsity() > threshold` returns the fully dense `Vec` (`Either::Right`), otherwise
+    /// the overlay unchanged (`Either::Left`).
+    pub fn scan_and_evict(self, threshold: f64, proto_ids: &[usize]) -> Either<Overlay<T>, Vec<T>> {
+        if self.sparsity() > threshold {
+            Either::Right(self.upgrade_to_fully_dense(proto_ids))
+        } else {
+            Either::Left(self)
+        }
+    }
+
+    /// Iterates overrides as `(instance_id, value)`, in `HashMap` (unspecified) order.
+    ///
+    /// See `iter_sparse_sorted` for a deterministic order.
+    pub fn iter_overrides(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.sparse_data.iter().map(|(&instance_id, value)| (instance_id, value))
+    }
+
+    /// Iterates overrides as `(instance_id, value)` in ascending `instance_id` order.
+    ///
+    /// Ids come from scanning the presence bitmask rather than `sparse_data`, so the order is
+    /// deterministic (unlike `HashMap` iteration). Prefer this when results must be reproducible.
+    pub fn iter_sparse_sorted(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.presence
+            .iter()
+            .enumerate()
+            .flat_map(|(word, &bits)| {
+                let mut bits = bits;
+                std::iter::from_fn(move || {
+                    if bits == 0 {
+                        return None;
+                    }
+                    let bit = bits.trailing_zeros() as usize;
+                    bits &= bits - 1;
+                    Some((word << 6) | bit)
+                })
+            })
+            .map(|instance_id| {
+                let value = self
+                    .sparse_data
+                    .get(&instance_id)
+                    .expect("Overlay presence bit set but sparse_data missing entry");
+                (instance_id, value)
+            })
+    }
+
+    /// Serializes only the overrides that differ from their prototype, as a map of
+    /// `instance_id -> value` in ascending order. `proto_ids[i]` is instance `i`'s prototype.
+    ///
+    /// Compact wire format for syncing state; apply it with `deserialize_delta`.
+    pub fn serialize_delta<S: Serializer>(
+        &self,
+        proto_ids: &[usize],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Serialize + PartialEq,
+    {
+        serializer.collect_map(
+            self.iter_sparse_sorted()
+                .filter(|&(instance_id, value)| *value != self.dense_data[proto_ids[instance_id]]),
+        )
+    }
+
+    /// Applies a delta written by `serialize_delta`: listed instances get the value as an
+    /// override, every other instance is reset to its prototype.
+    ///
+    /// Overrides go through `clear_override`/`set`, so hooks fire and the insertion order is
+    /// kept up to date. Pending `mark_for_write` marks are dropped, since the delta replaces
+    /// the whole override state.
+    pub fn deserialize_delta<'de, D: Deserializer<'de>>(&mut self, deserializer: D) -> Result<(), D::Error>
+    where
+        T: Deserialize<'de>,
+    {
+        let delta = BTreeMap::<usize, T>::deserialize(deserializer)?;
+        if let Some(&instance_id) = delta.keys().next_back()
+            && instance_id >= self.instance_len
+        {
+            return Err(serde::de::Error::custom(format!(
+                "Overlay delta instance out of bounds: {instance_id} >= {}",
+                self.instance_len
+            )));
+        }
+
+        let stale: Vec<usize> = self
+            .sparse_data
+            .keys()
+            .copied()
+            .filter(|instance_id| !delta.contains_key(instance_id))
+            .collect();
+        for instance_id in stale {
+            self.clear_override(instance_id);
+        }
+        self.lazy_marks.clear();
+        for (instance_id, value) in delta {
+            self.set(instance_id, value);
+        }
+        Ok(())
+    }
+
+    /// Iterates overrides as `(instance_id, value)` in the order they were first set.
+    ///
+    /// Removing an override keeps the order of the rest; when `swap_remove_instance` moves the
+    /// last instance into a hole, its override keeps its place under the new id.
+    #[cfg(feature = "ordered_overrides")]
+    pub fn iter_overrides_insertion_order(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.override_order.iter().map(|&instance_id| (instance_id, &self.sparse_data[&instance_id]))
+    }
+
+    /// The earliest-set override still present, in O(1).
+    #[cfg(feature = "ordered_overrides")]
+    pub fn first_override(&self) -> Option<(usize, &T)> {
+        let &instance_id = self.override_order.first()?;
+        Some((instance_id, &self.sparse_data[&instance_id]))
+    }
+
+    /// The most recently set override still present, in O(1).
+    #[cfg(feature = "ordered_overrides")]
+    pub fn last_override(&self) -> Option<(usize, &T)> {
+        let &instance_id = self.override_order.last()?;
+        Some((instance_id, &self.sparse_data[&instance_id]))
+    }
+
+    /// Panics if any `sparse_data` key is outside the tracked instance range.
+    ///
+    /// Such entries can never be reached by `get` and indicate `instance_len` got out of sync.
+    pub fn assert_no_dangling_overrides(&self) {
+        for &instance_id in self.sparse_data.keys() {
+            assert!(
+                instance_id < self.instance_len,
+                "Overlay dangling override: {instance_id} >= {}",
+                self.instance_len
+            );
+        }
+    }
+
+    /// Panics if the presence bitmask and `sparse_data` disagree.
+    pub fn check_invariants(&self) {
+        for instance_id in 0..self.instance_len {
+            assert_eq!(
+                self.has_override(instance_id),
+                self.sparse_data.contains_key(&instance_id),
+                "Overlay presence bit does not match sparse_data for instance {instance_id}"
+            );
+        }
+
+        #[cfg(feature = "ordered_overrides")]
+        assert_eq!(
+            self.override_order.len(),
+            self.sparse_data.len(),
+            "Overlay override_order out of sync with sparse_data"
+        );
+
+        assert!(
+            self.lazy_marks.len() <= self.instance_len,
+            "Overlay lazy_marks past instance_len: {} > {}",
+            self.lazy_marks.len(),
+            self.instance_len
+        );
+
+        self.assert_no_dangling_overrides();
+    }
+}
+
+/// The overrides of an `Overlay` at one point in time, from `Overlay::snapshot`.
+#[derive(Clone)]
+pub struct OverlaySnapshot<T> {
+    presence: Vec<u64>,
+    sparse_data: HashMap<usize, T>,
+}
+
+/// Sizes and capacities of an `Overlay`'s internals, from `Overlay::storage_stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct OverlayStats {
+    pub prototype_count: usize,
+    pub instance_count: usize,
+    pub override_count: usize,
+    pub dense_capacity: usize,
+    pub sparse_capacity: usize,
+    pub presence_words: usize,
+}
+
+impl fmt::Display for OverlayStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} prototypes (capacity {}), {} instances, {} overrides (capacity {}), {} presence words",
+            self.prototype_count,
+            self.dense_capacity,
+            self.instance_count,
+            self.override_count,
+            self.sparse_capacity,
+            self.presence_words
+        )
+    }
+}
+
+/// One of two values, e.g. the result of `Overlay::scan_and_evict`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+/// Iterator from `Overlay::drain_instances`: `(instance_id, override)` for each removed
+/// instance, in order, with `None` for instances that used their prototype's value.
+///
+/// The instances are already removed when this is created; dropping it early drops the rest.
+pub struct DrainInstances<'a, T> {
+    drained: std::vec::IntoIter<(usize, Option<T>)>,
+    _overlay: PhantomData<&'a mut Overlay<T>>,
+}
+
+impl<T> Iterator for DrainInstances<'_, T> {
+    type Item = (usize, Option<T>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.drained.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drained.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for DrainInstances<'_, T> {}
+
+/// Write handle from `Overlay::get_lazy_mut` that defers copy-on-write until first mutation.
+pub struct LazyMut<'a, T: Clone> {
+    overlay: &'a mut Overlay<T>,
+    instance_id: usize,
+    prototype_id: usize,
+}
+
+impl<T: Clone> std::ops::Deref for LazyMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.overlay.get(self.instance_id, self.prototype_id)
+    }
+}
+
+impl<T: Clone> std::ops::DerefMut for LazyMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.overlay.get_mut(self.instance_id, self.prototype_id)
+    }
+}
+
+/// An `Overlay<T>` paired with a function computing an instance's prototype_id.
+///
+/// Useful when prototype ids follow a pattern (e.g. everything shares prototype 0), so call sites
+/// don't need to pass `prototype_id[i]` to every read/write.
+pub struct OverlayWithProtoFn<T, F: Fn(usize) -> usize> {
+    pub overlay: Overlay<T>,
+    pub prototype_id_fn: F,
+}
+
+impl<T, F> OverlayWithProtoFn<T, F>
+where
+    T: Clone,
+    F: Fn(usize) -> usize,
+{
+    pub fn new(overlay: Overlay<T>, prototype_id_fn: F) -> Self {
+        Self { overlay, prototype_id_fn }
+    }
+
+    /// Read with fallback to the prototype chosen by `prototype_id_fn`.
+    pub fn get(&self, instance_id: usize) -> &T {
+        self.overlay.get(instance_id, (self.prototype_id_fn)(instance_id))
+    }
+
+    /// Write access with copy-on-write from the prototype chosen by `prototype_id_fn`.
+    pub fn get_mut(&mut self, instance_id: usize) -> &mut T {
+        let prototype_id = (self.prototype_id_fn)(instance_id);
+        self.overlay.get_mut(instance_id, prototype_id)
+    }
+}
+
+/// An `Overlay<T>` with optional per-instance metadata (last-modified tick, network sequence
+/// number, ...) kept in sync on push and swap-remove.
+pub struct OverlayWithMeta<T, M> {
+    pub overlay: Overlay<T>,
+
+    /// Metadata per instance (indexed by `instance_id`).
+    pub meta: Vec<Option<M>>,
+}
+
+impl<T, M> Default for OverlayWithMeta<T, M> {
+    fn default() -> Self {
+        Self {
+            overlay: Overlay::default(),
+            meta: Vec::new(),
+        }
+    }
+}
+
+impl<T, M> OverlayWithMeta<T, M>
+where
+    T: Clone,
+{
+    /// Adds a new instance slot (no override or metadata set).
+    pub fn push_instance(&mut self) {
+        self.overlay.push_instance();
+        self.meta.push(None);
+    }
+
+    /// Sets an override value and its metadata for `instance_id`.
+    pub fn set_with_meta(&mut self, instance_id: usize, value: T, meta: M) {
+        self.overlay.set(instance_id, value);
+        self.meta[instance_id] = Some(meta);
+    }
+
+    /// Metadata for `instance_id`, if any was set.
+    pub fn get_meta(&self, instance_id: usize) -> Option<&M> {
+        self.meta.get(instance_id).and_then(Option::as_ref)
+    }
+
+    /// Swap-remove an instance slot from both the overlay and the metadata.
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        self.overlay.swap_remove_instance(index);
+        self.meta.swap_remove(index);
+    }
+}
+
+/// Somewhere prototype values can be looked up by prototype_id.
+///
+/// Lets `Overlay::get_with_source` fall back to something other than the overlay's own
+/// `dense_data`, e.g. a `ChainedSource` for multi-level prototypes (base stats -> difficulty
+/// modifier -> per-level stats).
+pub trait PrototypeSource<T> {
+    /// The prototype value for `proto_id`, or `None` if this source doesn't define it.
+    fn try_get_prototype(&self, proto_id: usize) -> Option<&T>;
+
+    /// The prototype value for `proto_id`; panics if this source doesn't define it.
+    fn get_prototype(&self, proto_id: usize) -> &T {
+        self.try_get_prototype(proto_id)
+            .unwrap_or_else(|| panic!("PrototypeSource has no prototype {proto_id}"))
+    }
+}
+
+impl<T> PrototypeSource<T> for [T] {
+    fn try_get_prototype(&self, proto_id: usize) -> Option<&T> {
+        self.get(proto_id)
+    }
+}
+
+impl<T> PrototypeSource<T> for Vec<T> {
+    fn try_get_prototype(&self, proto_id: usize) -> Option<&T> {
+        self.get(proto_id)
+    }
+}
+
+impl<T> PrototypeSource<T> for HashMap<usize, T> {
+    fn try_get_prototype(&self, proto_id: usize) -> Option<&T> {
+        self.get(&proto_id)
+    }
+}
+
+impl<T> PrototypeSource<T> for Overlay<T> {
+    fn try_get_prototype(&self, proto_id: usize) -> Option<&T> {
+        self.dense_data.get(proto_id)
+    }
+}
+
+impl<T, S: PrototypeSource<T> + ?Sized> PrototypeSource<T> for &S {
+    fn try_get_prototype(&self, proto_id: usize) -> Option<&T> {
+        (**self).try_get_prototype(proto_id)
+    }
+}
+
+/// Two-level prototype inheritance: looks in `primary` first, then `fallback`.
+pub struct ChainedSource<A, B> {
+    pub primary: A,
+    pub fallback: B,
+}
+
+impl<T, A, B> PrototypeSource<T> for ChainedSource<A, B>
+where
+    A: PrototypeSource<T>,
+    B: PrototypeSource<T>,
+{
+    fn try_get_prototype(&self, proto_id: usize) -> Option<&T> {
+        self.primary
+            .try_get_prototype(proto_id)
+            .or_else(|| self.fallback.try_get_prototype(proto_id))
+    }
+}
+
+/// Like `Overlay<T>`, but for components that can't be cloned (OS handles etc).
+///
+/// Prototype values are reference counted, so `init_from_prototypes` shares them with the
+/// runtime table instead of cloning them, and spawned instances read the real prototype value.
+/// Instance values are boxed and never copied: there is no copy-on-write, so an instance has
+/// to be given its own value with `set` before it can be mutated.
+pub struct OverlayNoCopy<T> {
+    /// Prototype/template data (indexed by `prototype_id`), shared with runtime tables.
+    pub dense_data: Vec<Arc<T>>,
+
+    /// Per-instance values (keyed by `instance_id`).
+    pub sparse_data: HashMap<usize, Box<T>>,
+
+    /// Bitmask for which instances have values in `sparse_data`.
+    pub presence: Vec<u64>,
+
+    /// Prototype values kept by instances without their own value (see `pin`), so they don't
+    /// follow a prototype change.
+    pinned: HashMap<usize, Arc<T>>,
+
+    /// Logical number of instances being tracked by this overlay.
+    instance_len: usize,
+}
+
+impl<T> Default for OverlayNoCopy<T> {
+    fn default() -> Self {
+        Self {
+            dense_data: Vec::new(),
+            sparse_data: HashMap::new(),
+            presence: Vec::new(),
+            pinned: HashMap::new(),
+            instance_len: 0,
+        }
+    }
+}
+
+impl<T> OverlayNoCopy<T> {
+    #[inline]
+    fn word_bit(instance_id: usize) -> (usize, u64) {
+        (instance_id >> 6, 1u64 << (instance_id & 63))
+    }
+
+    /// Number of spawned instances represented by this overlay.
+    pub fn instances_len(&self) -> usize {
+        self.instance_len
+    }
+
+    /// Number of loaded prototypes/templates represented by this overlay.
+    pub fn prototypes_len(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    /// Adds a new instance slot (no value set).
+    pub fn push_instance(&mut self) {
+        let (word, _) = Self::word_bit(self.instance_len);
+        self.instance_len += 1;
+        if self.presence.len() <= word {
+            self.presence.resize(word + 1, 0);
+        }
+    }
+
+    /// Returns true if this instance has its own value.
+    pub fn has_override(&self, instance_id: usize) -> bool {
+        if instance_id >= self.instance_len {
+            return false;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        (self.presence.get(word).copied().unwrap_or(0) & mask) != 0
+    }
+
+    /// Whether this instance keeps a pinned prototype value (see `pin`).
+    pub fn is_pinned(&self, instance_id: usize) -> bool {
+        self.pinned.contains_key(&instance_id)
+    }
+
+    /// Read with fallback to the pinned, then prototype/template value.
+    pub fn get(&self, instance_id: usize, prototype_id: usize) -> &T {
+        if self.has_override(instance_id) {
+            return self
+                .sparse_data
+                .get(&instance_id)
+                .expect("OverlayNoCopy presence bit set but sparse_data missing entry");
+        }
+        if let Some(value) = self.pinned.get(&instance_id) {
+            return value;
+        }
+        &self.dense_data[prototype_id]
+    }
+
+    /// Write access to an instance's own value.
+    ///
+    /// Panics if the instance has no value yet (a pinned prototype value doesn't count); there
+    /// is no copy-on-write for `OverlayNoCopy`.
+    pub fn get_mut(&mut self, instance_id: usize) -> &mut T {
+        if !self.has_override(instance_id) {
+            panic!("OverlayNoCopy requires explicit set() before mutation");
+        }
+        self.sparse_data
+            .get_mut(&instance_id)
+            .expect("OverlayNoCopy presence bit set but sparse_data missing entry")
+    }
+
+    /// Sets the value for `instance_id` (marks presence bit, replacing any pinned value).
+    pub fn set(&mut self, instance_id: usize, value: Box<T>) {
+        if instance_id >= self.instance_len {
+            panic!("OverlayNoCopy set out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        self.pinned.remove(&instance_id);
+        self.sparse_data.insert(instance_id, value);
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word] |= mask;
+    }
+
+    /// Makes `instance_id` keep prototype `prototype_id`'s current value even if its prototype
+    /// changes, by sharing the value rather than cloning it. No-op if it has its own value.
+    pub fn pin(&mut self, instance_id: usize, prototype_id: usize) {
+        if instance_id >= self.instance_len {
+            panic!("OverlayNoCopy pin out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        if !self.has_override(instance_id) && !self.pinned.contains_key(&instance_id) {
+            self.pinned.insert(instance_id, Arc::clone(&self.dense_data[prototype_id]));
+        }
+    }
+
+    /// Clears the value (and any pinned value) for `instance_id`, returning its own value if
+    /// present.
+    pub fn clear_override(&mut self, instance_id: usize) -> Option<Box<T>> {
+        if instance_id >= self.instance_len {
+            return None;
+        }
+        self.pinned.remove(&instance_id);
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word] &= !mask;
+        self.sparse_data.remove(&instance_id)
+    }
+
+    /// Swap-remove an instance slot, keeping O(1) semantics consistent with `Vec::swap_remove`.
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        if index >= self.instance_len {
+            panic!(
+                "OverlayNoCopy swap_remove out of bounds: {index} >= {}",
+                self.instance_len
+            );
+        }
+
+        let last = self.instance_len - 1;
+        self.clear_override(index);
+
+        if index != last {
+            let pinned = self.pinned.remove(&last);
+            if let Some(v) = self.clear_override(last) {
+                self.set(index, v);
+            }
+            if let Some(p) = pinned {
+                self.pinned.insert(index, p);
+            }
+        }
+
+        self.instance_len -= 1;
+    }
+}
+
+impl<T> Storage<T> for OverlayNoCopy<T>
+where
+    T: Default + DeserializeOwned,
+{
+    fn init_from_prototypes(&mut self, prototypes: &Self) {
+        // Share the prototype values; they can't be cloned.
+        self.dense_data = prototypes.dense_data.clone();
+        self.sparse_data.clear();
+        self.presence.clear();
+        self.pinned.clear();
+        self.instance_len = 0;
+    }
+
+    fn push_json(&mut self, json: &Value) {
+        let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+        self.dense_data.push(Arc::new(val));
+    }
+
+    fn replace_json(&mut self, proto_idx: usize, json: &Value) {
+        self.dense_data[proto_idx] = Arc::new(serde_json::from_value::<T>(json.clone()).unwrap_or_default());
+    }
+
+    fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {
+        self.push_instance();
+    }
+
+    fn push_default(&mut self) {
+        self.push_instance();
+        self.set(self.instance_len - 1, Box::default());
+    }
+
+    fn swap_remove(&mut self, index: usize) {
+        self.swap_remove_instance(index);
+    }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T {
+        self.get(index, proto_idx)
+    }
+
+    fn override_state(&self, index: usize) -> Option<bool> {
+        Some(self.has_override(index) || self.is_pinned(index))
+    }
+
+    fn pin_entity(&mut self, index: usize, proto_idx: usize) {
+        self.pin(index, proto_idx);
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self.set(index, Box::new(value));
+    }
+
+    fn clear_entity_override(&mut self, index: usize) {
+        self.clear_override(index);
+    }
+
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.push_instance();
+        let new_idx = self.instance_len - 1;
+        if self.has_override(src_idx) {
+            let value = self.sparse_data[&src_idx].clone();
+            self.set(new_idx, value);
+        } else if let Some(pinned) = self.pinned.get(&src_idx) {
+            self.pinned.insert(new_idx, Arc::clone(pinned));
+        }
+    }
+
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        self.dense_data.extend(data.iter().cloned().map(Arc::new));
+    }
+
+    fn override_count(&self) -> usize {
+        self.sparse_data.len() + self.pinned.len()
+    }
+
+    fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        let ids: Vec<usize> = self
+            .sparse_data
+            .keys()
+            .chain(self.pinned.keys())
+            .copied()
+            .filter(|&i| entity_proto_ids[i] == proto_id)
+            .collect();
+        for &instance_id in &ids {
+            self.clear_override(instance_id);
+        }
+        ids.len()
+    }
+
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn swap_prototype_copies(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = Arc::new(f(value));
+        }
+    }
+
+    fn transform_prototype_copies(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = Arc::new(f(value));
+        }
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.instances_len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.prototypes_len())
+    }
+
+    fn row_capacity(&self) -> usize {
+        self.dense_data.capacity()
+    }
+
+    fn is_overlay() -> bool {
+        true
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&*self.dense_data[proto_idx]).expect("failed to serialize prototype field")
+    }
+}
+
+/// Like `Overlay<T>`, but for unsized values (`DynOverlay<dyn Component>`), e.g. plugin
+/// components whose concrete types are only known at runtime.
+///
+/// Values are boxed, and copy-on-write uses the `clone_fn` given at construction instead of
+/// `Clone` (which `dyn Trait` can't implement). There's no `Storage` impl, since values can't
+/// be deserialized or defaulted without knowing their type; load prototypes with
+/// `push_prototype`.
+pub struct DynOverlay<T: ?Sized> {
+    /// Prototype/template data (indexed by `prototype_id`).
+    pub dense_data: Vec<Box<T>>,
+
+    /// Per-instance overrides (keyed by `instance_id`).
+    pub sparse_data: HashMap<usize, Box<T>>,
+
+    /// Bitmask for which instances have overrides in `sparse_data`.
+    pub presence: Vec<u64>,
+
+    /// Logical number of instances being tracked by this overlay.
+    instance_len: usize,
+
+    /// Copy-on-write clone of a prototype value.
+    clone_fn: DynCloneFn<T>,
+}
+
+/// `DynOverlay` copy-on-write clone function.
+pub type DynCloneFn<T> = Box<dyn Fn(&T) -> Box<T> + Send + Sync>;
+
+impl<T: ?Sized> DynOverlay<T> {
+    /// An empty overlay that clones values for copy-on-write with `clone_fn`.
+    pub fn new(clone_fn: impl Fn(&T) -> Box<T> + Send + Sync + 'static) -> Self {
+        Self {
+            dense_data: Vec::new(),
+            sparse_data: HashMap::new(),
+            presence: Vec::new(),
+            instance_len: 0,
+            clone_fn: Box::new(clone_fn),
+        }
+    }
+
+    #[inline]
+    fn word_bit(instance_id: usize) -> (usize, u64) {
+        (instance_id >> 6, 1u64 << (instance_id & 63))
+    }
+
+    /// Number of spawned instances represented by this overlay.
+    pub fn instances_len(&self) -> usize {
+        self.instance_len
+    }
+
+    /// Number of loaded prototypes/templates represented by this overlay.
+    pub fn prototypes_len(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    /// Appends a prototype value, returning its prototype id.
+    pub fn push_prototype(&mut self, value: Box<T>) -> usize {
+        self.dense_data.push(value);
+        self.dense_data.len() - 1
+    }
+
+    /// Adds a new instance slot (no override set).
+    pub fn push_instance(&mut self) {
+        let (word, _) = Self::word_bit(self.instance_len);
+        self.instance_len += 1;
+        if self.presence.len() <= word {
+            self.presence.resize(word + 1, 0);
+        }
+    }
+
+    /// Returns true if this instance has an override.
+    pub fn has_override(&self, instance_id: usize) -> bool {
+        if instance_id >= self.instance_len {
+            return false;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word] & mask != 0
+    }
+
+    /// Read with fallback to prototype/template data.
+    pub fn get(&self, instance_id: usize, prototype_id: usize) -> &T {
+        if self.has_override(instance_id) {
+            return self
+                .sparse_data
+                .get(&instance_id)
+                .expect("DynOverlay presence bit set but sparse_data missing entry");
+        }
+        &self.dense_data[prototype_id]
+    }
+
+    /// Write access with copy-on-write (through `clone_fn`) from the prototype/template.
+    pub fn get_mut(&mut self, instance_id: usize, prototype_id: usize) -> &mut T {
+        if !self.has_override(instance_id) {
+            let value = (self.clone_fn)(&self.dense_data[prototype_id]);
+            self.set(instance_id, value);
+        }
+        self.sparse_data
+            .get_mut(&instance_id)
+            .expect("DynOverlay write: sparse_data missing entry after insert")
+    }
+
+    /// Sets an override value for `instance_id` (marks presence bit).
+    pub fn set(&mut self, instance_id: usize, value: Box<T>) {
+        if instance_id >= self.instance_len {
+            panic!("DynOverlay set out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        self.sparse_data.insert(instance_id, value);
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word] |= mask;
+    }
+
+    /// Clears the override for `instance_id`, returning it if present.
+    pub fn clear_override(&mut self, instance_id: usize) -> Option<Box<T>> {
+        if instance_id >= self.instance_len {
+            return None;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word] &= !mask;
+        self.sparse_data.remove(&instance_id)
+    }
+
+    /// Swap-remove an instance slot, keeping O(1) semantics consistent with `Vec::swap_remove`.
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        if index >= self.instance_len {
+            panic!(
+                "DynOverlay swap_remove out of bounds: {index} >= {}",
+                self.instance_len
+            );
+        }
+
+        let last = self.instance_len - 1;
+        self.clear_override(index);
+
+        if index != last
+            && let Some(v) = self.clear_override(last)
+        {
+            self.set(index, v);
+        }
+
+        self.instance_len -= 1;
+    }
+}
+
+/// Like `Overlay<T>`, but prototype data is shared (`Arc`) instead of cloned per runtime table.
+///
+/// `init_from_prototypes` just bumps the refcount, so many runtime tables can share one large
+/// read-only prototype array. Loading prototypes into a table whose data is already shared
+/// clones it first (`Arc::make_mut`).
+#[derive(Clone)]
+pub struct OverlayShared<T> {
+    /// Prototype/template data (indexed by `prototype_id`), shared between tables.
+    pub dense_data: Arc<Vec<T>>,
+
+    /// Per-instance overrides and presence bits (its own `dense_data` is unused).
+    overrides: Overlay<T>,
+}
+
+impl<T> Default for OverlayShared<T> {
+    fn default() -> Self {
+        Self {
+            dense_data: Arc::new(Vec::new()),
+            overrides: Overlay::default(),
+        }
+    }
+}
+
+impl<T> From<Overlay<T>> for OverlayShared<T> {
+    /// Moves the overlay's prototype data into an `Arc` (no clone); overrides are kept.
+    fn from(mut overlay: Overlay<T>) -> Self {
+        let dense_data = Arc::new(std::mem::take(&mut overlay.dense_data));
+        Self { dense_data, overrides: overlay }
+    }
+}
+
+impl<T> OverlayShared<T>
+where
+    T: Clone,
+{
+    /// Constructs a runtime overlay sharing `prototypes`' data.
+    pub fn new_from_prototypes_shared(prototypes: &OverlayShared<T>) -> Self {
+        Self {
+            dense_data: Arc::clone(&prototypes.dense_data),
+            overrides: Overlay::default(),
+        }
+    }
+
+    /// Number of spawned instances represented by this overlay.
+    pub fn instances_len(&self) -> usize {
+        self.overrides.instances_len()
+    }
+
+    /// Number of loaded prototypes/templates represented by this overlay.
+    pub fn prototypes_len(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    /// Adds a new instance slot (no override set).
+    pub fn push_instance(&mut self) {
+        self.overrides.push_instance();
+    }
+
+    /// Returns true if this instance has an override.
+    pub fn has_override(&self, instance_id: usize) -> bool {
+        self.overrides.has_override(instance_id)
+    }
+
+    /// Clears an override for `instance_id`, if present.
+    pub fn clear_override(&mut self, instance_id: usize) {
+        self.overrides.clear_override(instance_id);
+    }
+
+    /// Read with fallback to the shared prototype/template data.
+    pub fn get(&self, instance_id: usize, prototype_id: usize) -> &T {
+        if self.has_override(instance_id) {
+            return self
+                .overrides
+                .sparse_data
+                .get(&instance_id)
+                .expect("OverlayShared presence bit set but sparse_data missing entry");
+        }
+        &self.dense_data[prototype_id]
+    }
+
+    /// Write access with copy-on-write from the shared prototype/template.
+    pub fn get_mut(&mut self, instance_id: usize, prototype_id: usize) -> &mut T {
+        if !self.has_override(instance_id) {
+            self.overrides.set(instance_id, self.dense_data[prototype_id].clone());
+        }
+        self.overrides
+            .sparse_data
+            .get_mut(&instance_id)
+            .expect("OverlayShared write: sparse_data missing entry after insert")
+    }
+
+    /// Sets an override value for `instance_id` (marks presence bit).
+    pub fn set(&mut self, instance_id: usize, value: T) {
+        self.overrides.set(instance_id, value);
+    }
+
+    /// Swap-remove an instance slot, keeping O(1) semantics consistent with `Vec::swap_remove`.
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        self.overrides.swap_remove_instance(index);
+    }
+}
+
+impl<T> Storage<T> for OverlayShared<T>
+where
+    T: Clone + Default + DeserializeOwned,
+{
+    fn init_from_prototypes(&mut self, prototypes: &Self) {
+        *self = Self::new_from_prototypes_shared(prototypes);
+    }
+
+    fn push_json(&mut self, json: &Value) {
+        let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+        Arc::make_mut(&mut self.dense_data).push(val);
+    }
+
+    fn replace_json(&mut self, proto_idx: usize, json: &Value) {
+        Arc::make_mut(&mut self.dense_data)[proto_idx] = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+    }
+
+    fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {
+        self.push_instance();
+    }
+
+    fn push_default(&mut self) {
+        self.push_instance();
+        self.set(self.instances_len() - 1, T::default());
+    }
+
+    fn swap_remove(&mut self, index: usize) {
+        self.swap_remove_instance(index);
+    }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T {
+        self.get(index, proto_idx)
+    }
+
+    fn override_state(&self, index: usize) -> Option<bool> {
+        Some(self.has_override(index))
+    }
+
+    fn pin_entity(&mut self, index: usize, proto_idx: usize) {
+        self.get_mut(index, proto_idx);
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self.set(index, value);
+    }
+
+    fn clear_entity_override(&mut self, index: usize) {
+        self.clear_override(index);
+    }
+
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.overrides.clone_instance(src_idx);
+    }
+
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        Arc::make_mut(&mut self.dense_data).extend_from_slice(data);
+    }
+
+    fn override_count(&self) -> usize {
+        self.overrides.override_count()
+    }
+
+    fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        self.overrides.clear_overrides_for_prototype(proto_id, entity_proto_ids)
+    }
+
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        Arc::make_mut(&mut self.dense_data).swap(a, b);
+    }
+
+    fn swap_prototype_copies(&mut self, a: usize, b: usize) {
+        Arc::make_mut(&mut self.dense_data).swap(a, b);
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in Arc::make_mut(&mut self.dense_data) {
+            *value = f(value);
+        }
+    }
+
+    fn transform_prototype_copies(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in Arc::make_mut(&mut self.dense_data) {
+            *value = f(value);
+        }
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.instances_len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.prototypes_len())
+    }
+
+    fn row_capacity(&self) -> usize {
+        self.dense_data.capacity()
+    }
+
+    fn is_overlay() -> bool {
+        true
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&self.dense_data[proto_idx]).expect("failed to serialize prototype field")
+    }
+}
+
+/// Like `Overlay<T>`, but the presence bitmask is run-length encoded.
+///
+/// Meant for extremely sparse overlays (a handful of overrides across millions of instances),
+/// where even one bit per instance is too much. Every presence lookup decodes the runs, so
+/// `has_override`/`get` are O(runs) instead of O(1).
+#[derive(Clone)]
+pub struct SparseOverlay<T> {
+    /// Prototype/template data (indexed by `prototype_id`).
+    pub dense_data: Vec<T>,
+
+    /// Per-instance overrides (keyed by `instance_id`).
+    pub sparse_data: HashMap<usize, T>,
+
+    /// Alternating run lengths, in presence words: zero words, then non-zero words, then zero
+    /// words... Words past the last run are zero.
+    runs: Vec<usize>,
+
+    /// The non-zero presence words, in order (one per word counted by a non-zero run).
+    words: Vec<u64>,
+
+    /// Logical number of instances being tracked by this overlay.
+    instance_len: usize,
+}
+
+impl<T> Default for SparseOverlay<T> {
+    fn default() -> Self {
+        Self {
+            dense_data: Vec::new(),
+            sparse_data: HashMap::new(),
+            runs: Vec::new(),
+            words: Vec::new(),
+            instance_len: 0,
+        }
+    }
+}
+
+impl<T> SparseOverlay<T>
+where
+    T: Clone,
+{
+    /// Converts an `Overlay<T>`, encoding its presence bitmask.
+    pub fn from_overlay(overlay: &Overlay<T>) -> Self {
+        let mut out = Self {
+            dense_data: overlay.dense_data.clone(),
+            sparse_data: overlay.sparse_data.clone(),
+            instance_len: overlay.instance_len,
+            ..Self::default()
+        };
+        out.encode(
+            overlay
+                .presence
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|&(_, w)| w != 0),
+        );
+        out
+    }
+
+    #[inline]
+    fn word_bit(instance_id: usize) -> (usize, u64) {
+        (instance_id >> 6, 1u64 << (instance_id & 63))
+    }
+
+    /// Presence word `word`, decoded from the runs.
+    fn word(&self, word: usize) -> u64 {
+        let mut pos = 0;
+        let mut lit = 0;
+        for run in self.runs.chunks(2) {
+            pos += run[0];
+            if word < pos {
+                return 0;
+            }
+            let len = run.get(1).copied().unwrap_or(0);
+            if word < pos + len {
+                return self.words[lit + word - pos];
+            }
+            pos += len;
+            lit += len;
+        }
+        0
+    }
+
+    /// Non-zero presence words as `(word_index, word)`, ascending.
+    fn decode(&self) -> Vec<(usize, u64)> {
+        let mut out = Vec::with_capacity(self.words.len());
+        let mut pos = 0;
+        let mut lit = 0;
+        for run in self.runs.chunks(2) {
+            pos += run[0];
+            let len = run.get(1).copied().unwrap_or(0);
+            for i in 0..len {
+                out.push((pos + i, self.words[lit + i]));
+            }
+            pos += len;
+            lit += len;
+        }
+        out
+    }
+
+    /// Rebuilds the runs from ascending non-zero `(word_index, word)` pairs.
+    fn encode(&mut self, words: impl IntoIterator<Item = (usize, u64)>) {
+        self.runs.clear();
+        self.words.clear();
+        let mut end = 0;
+        for (index, word) in words {
+            if index == end && !self.runs.is_empty() {
+                *self.runs.last_mut().unwrap() += 1;
+            } else {
+                self.runs.push(index - end);
+                self.runs.push(1);
+            }
+            self.words.push(word);
+            end = index + 1;
+        }
+    }
+
+    fn set_word(&mut self, word: usize, value: u64) {
+        let mut words = self.decode();
+        match words.binary_search_by_key(&word, |&(i, _)| i) {
+            Ok(pos) if value == 0 => {
+                words.remove(pos);
+            }
+            Ok(pos) => words[pos].1 = value,
+            Err(_) if value == 0 => return,
+            Err(pos) => words.insert(pos, (word, value)),
+        }
+        self.encode(words);
+    }
+
+    /// Number of spawned instances represented by this overlay.
+    pub fn instances_len(&self) -> usize {
+        self.instance_len
+    }
+
+    /// Number of loaded prototypes/templates represented by this overlay.
+    pub fn prototypes_len(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    /// Adds a new instance slot (no override set). Free: zero words aren't stored.
+    pub fn push_instance(&mut self) {
+        self.instance_len += 1;
+    }
+
+    /// Returns true if this instance has an override.
+    pub fn has_override(&self, instance_id: usize) -> bool {
+        if instance_id >= self.instance_len {
+            return false;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        self.word(word) & mask != 0
+    }
+
+    /// Clears an override for `instance_id`, if present.
+    pub fn clear_override(&mut self, instance_id: usize) {
+        if !self.has_override(instance_id) {
+            return;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        self.set_word(word, self.word(word) & !mask);
+        self.sparse_data.remove(&instance_id);
+    }
+
+    /// Read with fallback to prototype/template data.
+    pub fn get(&self, instance_id: usize, prototype_id: usize) -> &T {
+        if self.has_override(instance_id) {
+            return self
+                .sparse_data
+                .get(&instance_id)
+                .expect("SparseOverlay presence bit set but sparse_data missing entry");
+        }
+        &self.dense_data[prototype_id]
+    }
+
+    /// Write access with copy-on-write from the prototype/template.
+    pub fn get_mut(&mut self, instance_id: usize, prototype_id: usize) -> &mut T {
+        if !self.has_override(instance_id) {
+            self.set(instance_id, self.dense_data[prototype_id].clone());
+        }
+        self.sparse_data
+            .get_mut(&instance_id)
+            .expect("SparseOverlay write: sparse_data missing entry after insert")
+    }
+
+    /// Sets an override value for `instance_id` (marks presence bit).
+    pub fn set(&mut self, instance_id: usize, value: T) {
+        if instance_id >= self.instance_len {
+            panic!("SparseOverlay set out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        self.sparse_data.insert(instance_id, value);
+        let (word, mask) = Self::word_bit(instance_id);
+        self.set_word(word, self.word(word) | mask);
+    }
+
+    /// Swap-remove an instance slot, keeping O(1) semantics consistent with `Vec::swap_remove`
+    /// (apart from decoding the runs).
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        if index >= self.instance_len {
+            panic!(
+                "SparseOverlay swap_remove out of bounds: {index} >= {}",
+                self.instance_len
+            );
+        }
+
+        let last = self.instance_len - 1;
+        self.clear_override(index);
+
+        if index != last
+            && self.has_override(last)
+            && let Some(v) = self.sparse_data.remove(&last)
+        {
+            let (word, mask) = Self::word_bit(last);
+            self.set_word(word, self.word(word) & !mask);
+            self.set(index, v);
+        }
+
+        self.instance_len -= 1;
+    }
+}
+
+impl<T> Storage<T> for SparseOverlay<T>
+where
+    T: Clone + Default + DeserializeOwned,
+{
+    fn init_from_prototypes(&mut self, prototypes: &Self) {
+        *self = Self {
+            dense_data: prototypes.dense_data.clone(),
+            ..Self::default()
+        };
+    }
+
+    fn push_json(&mut self, json: &Value) {
+        let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+        self.dense_data.push(val);
+    }
+
+    fn replace_json(&mut self, proto_idx: usize, json: &Value) {
+        self.dense_data[proto_idx] = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+    }
+
+    fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {
+        self.push_instance();
+    }
+
+    fn push_default(&mut self) {
+        self.push_instance();
+        self.set(self.instance_len - 1, T::default());
+    }
+
+    fn swap_remove(&mut self, index: usize) {
+        self.swap_remove_instance(index);
+    }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T {
+        self.get(index, proto_idx)
+    }
+
+    fn override_state(&self, index: usize) -> Option<bool> {
+        Some(self.has_override(index))
+    }
+
+    fn pin_entity(&mut self, index: usize, proto_idx: usize) {
+        self.get_mut(index, proto_idx);
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self.set(index, value);
+    }
+
+    fn clear_entity_override(&mut self, index: usize) {
+        self.clear_override(index);
+    }
+
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.push_instance();
+        if self.has_override(src_idx) {
+            let value = self.sparse_data[&src_idx].clone();
+            self.set(self.instance_len - 1, value);
+        }
+    }
+
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        self.dense_data.extend_from_slice(data);
+    }
+
+    fn override_count(&self) -> usize {
+        self.sparse_data.len()
+    }
+
+    fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        let ids: Vec<usize> = self
+            .sparse_data
+            .keys()
+            .copied()
+            .filter(|&i| entity_proto_ids[i] == proto_id)
+            .collect();
+        for &instance_id in &ids {
+            self.clear_override(instance_id);
+        }
+        ids.len()
+    }
+
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn swap_prototype_copies(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn transform_prototype_copies(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.instances_len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.prototypes_len())
+    }
+
+    fn row_capacity(&self) -> usize {
+        self.dense_data.capacity()
+    }
+
+    fn is_overlay() -> bool {
+        true
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&self.dense_data[proto_idx]).expect("failed to serialize prototype field")
+    }
+}
+
+/// Number of overrides `SmallOverlay<T>` stores inline before moving them to a `HashMap`.
+pub const SMALL_OVERLAY_INLINE: usize = 4;
+
+/// Like `Overlay<T>`, but the first `SMALL_OVERLAY_INLINE` overrides live in an inline array.
+///
+/// Most small-game fields only ever get a few overrides, and `HashMap` allocates on its first
+/// insert. The inline slots are searched linearly; the override that doesn't fit moves all of
+/// them to the map.
+#[derive(Clone)]
+pub struct SmallOverlay<T> {
+    /// Prototype/template data (indexed by `prototype_id`).
+    pub dense_data: Vec<T>,
+
+    /// Presence bitmask for instances (1 bit per `instance_id`).
+    pub presence: Vec<u64>,
+
+    /// Inline overrides, used while `spilled` is empty.
+    inline: [Option<(usize, T)>; SMALL_OVERLAY_INLINE],
+
+    /// Every override, once there are too many for `inline`.
+    spilled: HashMap<usize, T>,
+
+    /// Logical number of instances being tracked by this overlay.
+    instance_len: usize,
+}
+
+impl<T> Default for SmallOverlay<T> {
+    fn default() -> Self {
+        Self {
+            dense_data: Vec::new(),
+            presence: Vec::new(),
+            inline: [const { None }; SMALL_OVERLAY_INLINE],
+            spilled: HashMap::new(),
+            instance_len: 0,
+        }
+    }
+}
+
+impl<T> SmallOverlay<T>
+where
+    T: Clone,
+{
+    #[inline]
+    fn word_bit(instance_id: usize) -> (usize, u64) {
+        (instance_id >> 6, 1u64 << (instance_id & 63))
+    }
+
+    fn find(&self, instance_id: usize) -> Option<&T> {
+        if !self.spilled.is_empty() {
+            return self.spilled.get(&instance_id);
+        }
+        self.inline
+            .iter()
+            .flatten()
+            .find(|(i, _)| *i == instance_id)
+            .map(|(_, v)| v)
+    }
+
+    fn find_mut(&mut self, instance_id: usize) -> Option<&mut T> {
+        if !self.spilled.is_empty() {
+            return self.spilled.get_mut(&instance_id);
+        }
+        self.inline
+            .iter_mut()
+            .flatten()
+            .find(|(i, _)| *i == instance_id)
+            .map(|(_, v)| v)
+    }
+
+    fn insert(&mut self, instance_id: usize, value: T) {
+        if let Some(slot) = self.find_mut(instance_id) {
+            *slot = value;
+            return;
+        }
+        if self.spilled.is_empty() {
+            if let Some(slot) = self.inline.iter_mut().find(|s| s.is_none()) {
+                *slot = Some((instance_id, value));
+                return;
+            }
+            self.spilled.extend(self.inline.iter_mut().filter_map(Option::take));
+        }
+        self.spilled.insert(instance_id, value);
+    }
+
+    fn remove(&mut self, instance_id: usize) -> Option<T> {
+        if !self.spilled.is_empty() {
+            return self.spilled.remove(&instance_id);
+        }
+        self.inline
+            .iter_mut()
+            .find(|s| matches!(s, Some((i, _)) if *i == instance_id))?
+            .take()
+            .map(|(_, v)| v)
+    }
+
+    /// Whether the overrides have outgrown the inline slots and moved to the `HashMap`.
+    pub fn is_spilled(&self) -> bool {
+        !self.spilled.is_empty()
+    }
+
+    /// Number of spawned instances represented by this overlay.
+    pub fn instances_len(&self) -> usize {
+        self.instance_len
+    }
+
+    /// Number of loaded prototypes/templates represented by this overlay.
+    pub fn prototypes_len(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    /// Number of instances with an override.
+    pub fn override_count(&self) -> usize {
+        if self.is_spilled() {
+            self.spilled.len()
+        } else {
+            self.inline.iter().flatten().count()
+        }
+    }
+
+    /// Adds a new instance slot (no override set).
+    pub fn push_instance(&mut self) {
+        let (word, _) = Self::word_bit(self.instance_len);
+        self.instance_len += 1;
+        if self.presence.len() <= word {
+            self.presence.resize(word + 1, 0);
+        }
+    }
+
+    /// Returns true if this instance has an override.
+    pub fn has_override(&self, instance_id: usize) -> bool {
+        if instance_id >= self.instance_len {
+            return false;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word] & mask != 0
+    }
+
+    /// Clears an override for `instance_id`, if present.
+    pub fn clear_override(&mut self, instance_id: usize) {
+        if !self.has_override(instance_id) {
+            return;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word] &= !mask;
+        self.remove(instance_id);
+    }
+
+    /// Read with fallback to prototype/template data.
+    pub fn get(&self, instance_id: usize, prototype_id: usize) -> &T {
+        if self.has_override(instance_id) {
+            return self
+                .find(instance_id)
+                .expect("SmallOverlay presence bit set but override missing");
+        }
+        &self.dense_data[prototype_id]
+    }
+
+    /// Write access with copy-on-write from the prototype/template.
+    pub fn get_mut(&mut self, instance_id: usize, prototype_id: usize) -> &mut T {
+        if !self.has_override(instance_id) {
+            self.set(instance_id, self.dense_data[prototype_id].clone());
+        }
+        self.find_mut(instance_id)
+            .expect("SmallOverlay write: override missing after insert")
+    }
+
+    /// Sets an override value for `instance_id` (marks presence bit).
+    pub fn set(&mut self, instance_id: usize, value: T) {
+        if instance_id >= self.instance_len {
+            panic!("SmallOverlay set out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        self.insert(instance_id, value);
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word] |= mask;
+    }
+
+    /// Swap-remove an instance slot, keeping O(1) semantics consistent with `Vec::swap_remove`.
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        if index >= self.instance_len {
+            panic!(
+                "SmallOverlay swap_remove out of bounds: {index} >= {}",
+                self.instance_len
+            );
+        }
+
+        let last = self.instance_len - 1;
+        self.clear_override(index);
+
+        if index != last && self.has_override(last) {
+            let (word, mask) = Self::word_bit(last);
+            self.presence[word] &= !mask;
+            if let Some(v) = self.remove(last) {
+                self.set(index, v);
+            }
+        }
+
+        self.instance_len -= 1;
+    }
+}
+
+impl<T> Storage<T> for SmallOverlay<T>
+where
+    T: Clone + Default + DeserializeOwned,
+{
+    fn init_from_prototypes(&mut self, prototypes: &Self) {
+        *self = Self {
+            dense_data: prototypes.dense_data.clone(),
+            ..Self::default()
+        };
+    }
+
+    fn push_json(&mut self, json: &Value) {
+        let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+        self.dense_data.push(val);
+    }
+
+    fn replace_json(&mut self, proto_idx: usize, json: &Value) {
+        self.dense_data[proto_idx] = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+    }
+
+    fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {
+        self.push_instance();
+    }
+
+    fn push_default(&mut self) {
+        self.push_instance();
+        self.set(self.instance_len - 1, T::default());
+    }
+
+    fn swap_remove(&mut self, index: usize) {
+        self.swap_remove_instance(index);
+    }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T {
+        self.get(index, proto_idx)
+    }
+
+    fn override_state(&self, index: usize) -> Option<bool> {
+        Some(self.has_override(index))
+    }
+
+    fn pin_entity(&mut self, index: usize, proto_idx: usize) {
+        self.get_mut(index, proto_idx);
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self.set(index, value);
+    }
+
+    fn clear_entity_override(&mut self, index: usize) {
+        self.clear_override(index);
+    }
+
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.push_instance();
+        if self.has_override(src_idx) {
+            let value = self
+                .find(src_idx)
+                .expect("SmallOverlay presence bit set but override missing")
+                .clone();
+            self.set(self.instance_len - 1, value);
+        }
+    }
+
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        self.dense_data.extend_from_slice(data);
+    }
+
+    fn override_count(&self) -> usize {
+        SmallOverlay::override_count(self)
+    }
+
+    fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        let ids: Vec<usize> = (0..self.instance_len)
+            .filter(|&i| self.has_override(i) && entity_proto_ids[i] == proto_id)
+            .collect();
+        for &instance_id in &ids {
+            self.clear_override(instance_id);
+        }
+        ids.len()
+    }
+
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn swap_prototype_copies(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn transform_prototype_copies(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.instances_len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.prototypes_len())
+    }
+
+    fn row_capacity(&self) -> usize {
+        self.dense_data.capacity()
+    }
+
+    fn is_overlay() -> bool {
+        true
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&self.dense_data[proto_idx]).expect("failed to serialize prototype field")
+    }
+}
+
+/// Like `Overlay<T>`, but override keys are `i64`, so negative ids can name sentinel entities
+/// (environment, global state) that have no row in the table.
+///
+/// Ids `0..instances_len()` are the table's rows; negative ids are always valid and are never
+/// moved by `swap_remove_instance`. There is no presence bitmask: `has_override` is a HashMap
+/// lookup. The `Storage` impl only ever sees rows (non-negative ids).
+#[derive(Clone)]
+pub struct OverlayI64<T> {
+    /// Prototype/template data (indexed by `prototype_id`).
+    pub dense_data: Vec<T>,
+
+    /// Per-instance overrides (keyed by instance id, negative for sentinels).
+    pub sparse_data: HashMap<i64, T>,
+
+    /// Number of non-negative instance ids (rows) being tracked by this overlay.
+    instance_len: usize,
+}
+
+impl<T> Default for OverlayI64<T> {
+    fn default() -> Self {
+        Self {
+            dense_data: Vec::new(),
+            sparse_data: HashMap::new(),
+            instance_len: 0,
+        }
+    }
+}
+
+impl<T> OverlayI64<T>
+where
+    T: Clone,
+{
+    /// Number of spawned instances (rows) represented by this overlay.
+    pub fn instances_len(&self) -> usize {
+        self.instance_len
+    }
+
+    /// Number of loaded prototypes/templates represented by this overlay.
+    pub fn prototypes_len(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    /// Number of overrides held, sentinels included.
+    pub fn override_count(&self) -> usize {
+        self.sparse_data.len()
+    }
+
+    /// Adds a new instance slot (no override set).
+    pub fn push_instance(&mut self) {
+        self.instance_len += 1;
+    }
+
+    /// Whether `instance_id` is a sentinel (negative) or an existing row.
+    pub fn is_valid_id(&self, instance_id: i64) -> bool {
+        instance_id < 0 || (instance_id as u64) < self.instance_len as u64
+    }
+
+    /// Returns true if this instance has an override.
+    pub fn has_override(&self, instance_id: i64) -> bool {
+        self.sparse_data.contains_key(&instance_id)
+    }
+
+    /// Clears an override for `instance_id`, if present.
+    pub fn clear_override(&mut self, instance_id: i64) {
+        self.sparse_data.remove(&instance_id);
+    }
+
+    /// Read with fallback to prototype/template data.
+    pub fn get(&self, instance_id: i64, prototype_id: usize) -> &T {
+        self.sparse_data
+            .get(&instance_id)
+            .unwrap_or(&self.dense_data[prototype_id])
+    }
+
+    /// Write access with copy-on-write from the prototype/template.
+    pub fn get_mut(&mut self, instance_id: i64, prototype_id: usize) -> &mut T {
+        if !self.is_valid_id(instance_id) {
+            panic!("OverlayI64 get_mut out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        self.sparse_data
+            .entry(instance_id)
+            .or_insert_with(|| self.dense_data[prototype_id].clone())
+    }
+
+    /// Sets an override value for `instance_id`.
+    pub fn set(&mut self, instance_id: i64, value: T) {
+        if !self.is_valid_id(instance_id) {
+            panic!("OverlayI64 set out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        self.sparse_data.insert(instance_id, value);
+    }
+
+    /// Swap-remove a row, keeping O(1) semantics consistent with `Vec::swap_remove`.
+    /// Sentinel overrides are untouched.
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        if index >= self.instance_len {
+            panic!(
+                "OverlayI64 swap_remove out of bounds: {index} >= {}",
+                self.instance_len
+            );
+        }
+
+        let last = self.instance_len - 1;
+        self.sparse_data.remove(&(index as i64));
+        if index != last
+            && let Some(v) = self.sparse_data.remove(&(last as i64))
+        {
+            self.sparse_data.insert(index as i64, v);
+        }
+
+        self.instance_len -= 1;
+    }
+}
+
+impl<T> Storage<T> for OverlayI64<T>
+where
+    T: Clone + Default + DeserializeOwned,
+{
+    fn init_from_prototypes(&mut self, prototypes: &Self) {
+        *self = Self {
+            dense_data: prototypes.dense_data.clone(),
+            ..Self::default()
+        };
+    }
+
+    fn push_json(&mut self, json: &Value) {
+        let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+        self.dense_data.push(val);
+    }
+
+    fn replace_json(&mut self, proto_idx: usize, json: &Value) {
+        self.dense_data[proto_idx] = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+    }
+
+    fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {
+        self.push_instance();
+    }
+
+    fn push_default(&mut self) {
+        self.push_instance();
+        self.set(self.instance_len as i64 - 1, T::default());
+    }
+
+    fn swap_remove(&mut self, index: usize) {
+        self.swap_remove_instance(index);
+    }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T {
+        self.get(index as i64, proto_idx)
+    }
+
+    fn override_state(&self, index: usize) -> Option<bool> {
+        Some(self.has_override(index as i64))
+    }
+
+    fn pin_entity(&mut self, index: usize, proto_idx: usize) {
+        self.get_mut(index as i64, proto_idx);
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self.set(index as i64, value);
+    }
+
+    fn clear_entity_override(&mut self, index: usize) {
+        self.clear_override(index as i64);
+    }
+
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.push_instance();
+        if let Some(value) = self.sparse_data.get(&(src_idx as i64)).cloned() {
+            self.set(self.instance_len as i64 - 1, value);
+        }
+    }
+
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        self.dense_data.extend_from_slice(data);
+    }
+
+    fn override_count(&self) -> usize {
+        OverlayI64::override_count(self)
+    }
+
+    fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        let before = self.sparse_data.len();
+        self.sparse_data
+            .retain(|&id, _| id < 0 || entity_proto_ids[id as usize] != proto_id);
+        before - self.sparse_data.len()
+    }
+
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn swap_prototype_copies(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn transform_prototype_copies(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.instances_len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.prototypes_len())
+    }
+
+    fn row_capacity(&self) -> usize {
+        self.dense_data.capacity()
+    }
+
+    fn is_overlay() -> bool {
+        true
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&self.dense_data[proto_idx]).expect("failed to serialize prototype field")
+    }
+}
+
+/// A dense column kept sorted by value, for binary-search lookups (e.g. events by timestamp).
+///
+/// Inserts go to their sorted position and removals are a stable `Vec::remove`, so both are
+/// O(n) moves. The column is ordered by value, not by entity, so row `i` would not line up
+/// with row `i` of a table's other fields: it is a standalone column type, not `Storage`, and
+/// can't be used as an `impl_load_prototype!` field.
+#[derive(Clone, Debug)]
+pub struct SortedVec<T: Ord> {
+    inner: Vec<T>,
+}
+
+impl<T: Ord> Default for SortedVec<T> {
+    fn default() -> Self {
+        Self { inner: Vec::new() }
+    }
+}
+
+impl<T: Ord> SortedVec<T> {
+    /// Inserts `value` at its sorted position, returning that position.
+    pub fn insert(&mut self, value: T) -> usize {
+        let pos = match self.inner.binary_search(&value) {
+            Ok(pos) | Err(pos) => pos,
+        };
+        self.inner.insert(pos, value);
+        pos
+    }
+
+    /// Removes and returns the value at `index`, keeping the rest sorted.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.inner.remove(index)
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.inner
+    }
+}
+
+impl<T: Ord> std::ops::Deref for SortedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.inner
+    }
+}
+
+/// Like `Overlay<T>`, but overrides can be read and set concurrently through `&self`.
+///
+/// Presence bits are `AtomicU64`s, so the common "no override, read the prototype" path never
+/// touches a lock. Override values live in a `RwLock<HashMap>`, and that lock is what keeps the
+/// two in step: `set` and `clear_override` update the map and the presence bit while holding
+/// the write lock, so once a writer returns the bit and the map agree.
+///
+/// Only the lock-free `has_override` load (Relaxed) can see a stale bit, while a writer is in
+/// progress. `get` then either reads the prototype or takes the read lock and finds the map
+/// entry missing, falling back to the prototype; either way it returns a value that was current
+/// at some point during the write. The atomics carry no ordering of their own: all override data
+/// is read under the lock.
+///
+/// Adding/removing instances needs `&mut self`. Override values can't be borrowed out of the
+/// lock, so `get` returns a clone, and the `Storage::get_entity` impl panics for an entity with
+/// an override (use `get` instead).
+pub struct AtomicOverlay<T> {
+    /// Prototype/template data (indexed by `prototype_id`).
+    pub dense_data: Vec<T>,
+
+    /// Per-instance overrides (keyed by `instance_id`).
+    pub sparse_data: RwLock<HashMap<usize, T>>,
+
+    /// Bitmask for which instances have overrides in `sparse_data`.
+    pub presence: Vec<AtomicU64>,
+
+    /// Logical number of instances being tracked by this overlay.
+    instance_len: usize,
+}
+
+impl<T> Default for AtomicOverlay<T> {
+    fn default() -> Self {
+        Self {
+            dense_data: Vec::new(),
+            sparse_data: RwLock::new(HashMap::new()),
+            presence: Vec::new(),
+            instance_len: 0,
+        }
+    }
+}
+
+impl<T> AtomicOverlay<T>
+where
+    T: Clone,
+{
+    #[inline]
+    fn word_bit(instance_id: usize) -> (usize, u64) {
+        (instance_id >> 6, 1u64 << (instance_id & 63))
+    }
+
+    /// Number of spawned instances represented by this overlay.
+    pub fn instances_len(&self) -> usize {
+        self.instance_len
+    }
+
+    /// Number of loaded prototypes/templates represented by this overlay.
+    pub fn prototypes_len(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    /// Adds a new instance slot (no override set).
+    pub fn push_instance(&mut self) {
+        let (word, _) = Self::word_bit(self.instance_len);
+        self.instance_len += 1;
+        if self.presence.len() <= word {
+            self.presence.resize_with(word + 1, || AtomicU64::new(0));
+        }
+    }
+
+    /// Returns true if this instance has an override.
+    pub fn has_override(&self, instance_id: usize) -> bool {
+        if instance_id >= self.instance_len {
+            return false;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        self.presence[word].load(Ordering::Relaxed) & mask != 0
+    }
+
+    /// Read (cloned) with fallback to prototype/template data. Lock-free without an override.
+    pub fn get(&self, instance_id: usize, prototype_id: usize) -> T {
+        if self.has_override(instance_id)
+            && let Some(v) = self.sparse_data.read().unwrap().get(&instance_id)
+        {
+            return v.clone();
+        }
+        self.dense_data[prototype_id].clone()
+    }
+
+    /// Sets an override value for `instance_id` (marks presence bit).
+    pub fn set(&self, instance_id: usize, value: T) {
+        if instance_id >= self.instance_len {
+            panic!("AtomicOverlay set out of bounds: {instance_id} >= {}", self.instance_len);
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        let mut sparse_data = self.sparse_data.write().unwrap();
+        sparse_data.insert(instance_id, value);
+        self.presence[word].fetch_or(mask, Ordering::Relaxed);
+    }
+
+    /// Clears an override for `instance_id`, if present.
+    pub fn clear_override(&self, instance_id: usize) {
+        if instance_id >= self.instance_len {
+            return;
+        }
+        let (word, mask) = Self::word_bit(instance_id);
+        let mut sparse_data = self.sparse_data.write().unwrap();
+        sparse_data.remove(&instance_id);
+        self.presence[word].fetch_and(!mask, Ordering::Relaxed);
+    }
+
+    /// Swap-remove an instance slot, keeping O(1) semantics consistent with `Vec::swap_remove`.
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        if index >= self.instance_len {
+            panic!(
+                "AtomicOverlay swap_remove out of bounds: {index} >= {}",
+                self.instance_len
+            );
+        }
+
+        let last = self.instance_len - 1;
+        self.clear_override(index);
+
+        if index != last && self.has_override(last) {
+            let value = self.sparse_data.get_mut().unwrap().remove(&last);
+            let (word, mask) = Self::word_bit(last);
+            *self.presence[word].get_mut() &= !mask;
+            if let Some(v) = value {
+                self.set(index, v);
+            }
+        }
+
+        self.instance_len -= 1;
+    }
+}
+
+impl<T> Storage<T> for AtomicOverlay<T>
+where
+    T: Clone + Default + DeserializeOwned,
+{
+    fn init_from_prototypes(&mut self, prototypes: &Self) {
+        *self = Self {
+            dense_data: prototypes.dense_data.clone(),
+            ..Self::default()
+        };
+    }
+
+    fn push_json(&mut self, json: &Value) {
+        let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+        self.dense_data.push(val);
+    }
+
+    fn replace_json(&mut self, proto_idx: usize, json: &Value) {
+        self.dense_data[proto_idx] = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+    }
+
+    fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {
+        self.push_instance();
+    }
+
+    fn push_default(&mut self) {
+        self.push_instance();
+        self.set(self.instance_len - 1, T::default());
+    }
+
+    fn swap_remove(&mut self, index: usize) {
+        self.swap_remove_instance(index);
+    }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T {
+        if self.has_override(index) {
+            panic!("AtomicOverlay overrides are behind a lock and can't be borrowed; use get_entity_cow");
+        }
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity_cow(&self, index: usize, proto_idx: usize) -> Cow<'_, T> {
+        if self.has_override(index) {
+            Cow::Owned(self.get(index, proto_idx))
+        } else {
+            Cow::Borrowed(&self.dense_data[proto_idx])
+        }
+    }
+
+    fn override_state(&self, index: usize) -> Option<bool> {
+        Some(self.has_override(index))
+    }
+
+    fn pin_entity(&mut self, index: usize, proto_idx: usize) {
+        if !self.has_override(index) {
+            self.set(index, self.dense_data[proto_idx].clone());
+        }
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self.set(index, value);
+    }
+
+    fn clear_entity_override(&mut self, index: usize) {
+        self.clear_override(index);
+    }
+
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.push_instance();
+        let value = self.sparse_data.get_mut().unwrap().get(&src_idx).cloned();
+        if let Some(value) = value {
+            self.set(self.instance_len - 1, value);
+        }
+    }
+
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        self.dense_data.extend_from_slice(data);
+    }
+
+    fn override_count(&self) -> usize {
+        self.sparse_data.read().unwrap().len()
+    }
+
+    fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        let ids: Vec<usize> = self
+            .sparse_data
+            .get_mut()
+            .unwrap()
+            .keys()
+            .copied()
+            .filter(|&i| entity_proto_ids[i] == proto_id)
+            .collect();
+        for &instance_id in &ids {
+            self.clear_override(instance_id);
+        }
+        ids.len()
+    }
+
+    fn get_as_json(&self, idx: usize, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        // Overrides can't be borrowed out of the lock (see `get_entity`), so serialize a clone.
+        serde_json::to_value(self.get(idx, proto_idx)).expect("failed to serialize entity field")
+    }
+
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn swap_prototype_copies(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn transform_prototype_copies(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.instances_len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.prototypes_len())
+    }
+
+    fn row_capacity(&self) -> usize {
+        self.dense_data.capacity()
+    }
+
+    fn is_overlay() -> bool {
+        true
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&self.dense_data[proto_idx]).expect("failed to serialize prototype field")
+    }
+}
+
+/// Like `Overlay<T>`, but the overrides sit behind a `RwLock` so many readers (or one writer)
+/// can use them through `&self` from several threads.
+///
+/// Prototype data is read-only after loading, so it lives outside the lock. Reads and writes
+/// go through the RAII guards from `read()`/`write()`, which expose the usual `Overlay` API.
+/// As with `AtomicOverlay`, the `Storage::get_entity` impl panics for an entity with an override
+/// (use a guard instead).
+#[cfg(feature = "concurrent")]
+pub struct RwOverlay<T> {
+    /// Prototype/template data (indexed by `prototype_id`).
+    pub dense_data: Vec<T>,
+
+    /// Per-instance overrides and presence bits (its own `dense_data` is unused).
+    overrides: RwLock<Overlay<T>>,
+}
+
+#[cfg(feature = "concurrent")]
+impl<T> Default for RwOverlay<T> {
+    fn default() -> Self {
+        Self {
+            dense_data: Vec::new(),
+            overrides: RwLock::new(Overlay::default()),
+        }
+    }
+}
+
+/// Shared access to an `RwOverlay`'s overrides; holds the read lock until dropped.
+#[cfg(feature = "concurrent")]
+pub struct RwOverlayReadGuard<'a, T> {
+    dense_data: &'a [T],
+    overrides: std::sync::RwLockReadGuard<'a, Overlay<T>>,
+}
+
+/// Exclusive access to an `RwOverlay`'s overrides; holds the write lock until dropped.
+#[cfg(feature = "concurrent")]
+pub struct RwOverlayWriteGuard<'a, T> {
+    dense_data: &'a [T],
+    overrides: std::sync::RwLockWriteGuard<'a, Overlay<T>>,
+}
+
+#[cfg(feature = "concurrent")]
+impl<T> RwOverlay<T>
+where
+    T: Clone,
+{
+    /// Locks the overrides for reading, blocking while a writer holds them.
+    pub fn read(&self) -> RwOverlayReadGuard<'_, T> {
+        RwOverlayReadGuard {
+            dense_data: &self.dense_data,
+            overrides: self.overrides.read().unwrap(),
+        }
+    }
+
+    /// Locks the overrides for writing, blocking while anyone else holds them.
+    pub fn write(&self) -> RwOverlayWriteGuard<'_, T> {
+        RwOverlayWriteGuard {
+            dense_data: &self.dense_data,
+            overrides: self.overrides.write().unwrap(),
+        }
+    }
+
+    /// Lock-free access to the overrides, for when `&mut self` already proves exclusivity.
+    fn overrides_mut(&mut self) -> &mut Overlay<T> {
+        self.overrides.get_mut().unwrap()
+    }
+
+    /// Number of spawned instances represented by this overlay.
+    pub fn instances_len(&self) -> usize {
+        self.read().overrides.instances_len()
+    }
+
+    /// Number of loaded prototypes/templates represented by this overlay.
+    pub fn prototypes_len(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    /// Adds a new instance slot (no override set).
+    pub fn push_instance(&mut self) {
+        self.overrides_mut().push_instance();
+    }
+
+    /// Swap-remove an instance slot, keeping O(1) semantics consistent with `Vec::swap_remove`.
+    pub fn swap_remove_instance(&mut self, index: usize) {
+        self.overrides_mut().swap_remove_instance(index);
+    }
+}
+
+#[cfg(feature = "concurrent")]
+impl<T> RwOverlayReadGuard<'_, T>
+where
+    T: Clone,
+{
+    /// Returns true if this instance has an override.
+    pub fn has_override(&self, instance_id: usize) -> bool {
+        self.overrides.has_override(instance_id)
+    }
+
+    /// Read with fallback to prototype/template data.
+    pub fn get(&self, instance_id: usize, prototype_id: usize) -> &T {
+        if self.has_override(instance_id) {
+            return self
+                .overrides
+                .sparse_data
+                .get(&instance_id)
+                .expect("RwOverlay presence bit set but sparse_data missing entry");
+        }
+        &self.dense_data[prototype_id]
+    }
+}
+
+#[cfg(feature = "concurrent")]
+impl<T> RwOverlayWriteGuard<'_, T>
+where
+    T: Clone,
+{
+    /// Returns true if this instance has an override.
+    pub fn has_override(&self, instance_id: usize) -> bool {
+        self.overrides.has_override(instance_id)
+    }
+
+    /// Read with fallback to prototype/template data.
+    pub fn get(&self, instance_id: usize, prototype_id: usize) -> &T {
+        if self.has_override(instance_id) {
+            return self
+                .overrides
+                .sparse_data
+                .get(&instance_id)
+                .expect("RwOverlay presence bit set but sparse_data missing entry");
+        }
+        &self.dense_data[prototype_id]
     }
 
-    fn push_json(&mut self, json: &Value) {
-        let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
-        self.push(val);
+    /// Write access with copy-on-write from the prototype/template.
+    pub fn get_mut(&mut self, instance_id: usize, prototype_id: usize) -> &mut T {
+        if !self.has_override(instance_id) {
+            self.overrides.set(instance_id, self.dense_data[prototype_id].clone());
+        }
+        self.overrides
+            .sparse_data
+            .get_mut(&instance_id)
+            .expect("RwOverlay write: sparse_data missing entry after insert")
     }
 
-    fn push_from_prototype(&mut self, source: &Self, proto_idx: usize) {
-        self.push(source[proto_idx].clone());
+    /// Sets an override value for `instance_id` (marks presence bit).
+    pub fn set(&mut self, instance_id: usize, value: T) {
+        self.overrides.set(instance_id, value);
     }
 
-    fn swap_remove(&mut self, index: usize) {
-        self.swap_remove(index);
+    /// Clears an override for `instance_id`, if present.
+    pub fn clear_override(&mut self, instance_id: usize) {
+        self.overrides.clear_override(instance_id);
     }
 }
 
-// Implement for Overlay (Sparse/COW storage)
-impl<T> Storage<T> for Overlay<T> 
-where T: Clone + Default + DeserializeOwned 
+#[cfg(feature = "concurrent")]
+impl<T> Storage<T> for RwOverlay<T>
+where
+    T: Clone + Default + DeserializeOwned,
 {
     fn init_from_prototypes(&mut self, prototypes: &Self) {
-        // Copy prototype/template data; clear per-instance overrides.
-        self.dense_data = prototypes.dense_data.clone();
-        self.sparse_data.clear();
-        self.presence.clear();
-        self.instance_len = 0;
+        *self = Self {
+            dense_data: prototypes.dense_data.clone(),
+            ..Self::default()
+        };
     }
 
     fn push_json(&mut self, json: &Value) {
-        // For an overlay, loading a prototype appends to `dense_data` (the templates).
         let val = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
         self.dense_data.push(val);
     }
 
+    fn replace_json(&mut self, proto_idx: usize, json: &Value) {
+        self.dense_data[proto_idx] = serde_json::from_value::<T>(json.clone()).unwrap_or_default();
+    }
+
     fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {
-        // When spawning a LIVE entity, an Overlay doesn't copy prototype data yet.
-        // It just extends the instance bitmask (copy-on-write on first mutation).
         self.push_instance();
     }
 
+    fn push_default(&mut self) {
+        let overrides = self.overrides_mut();
+        overrides.push_instance();
+        overrides.set(overrides.instances_len() - 1, T::default());
+    }
+
     fn swap_remove(&mut self, index: usize) {
         self.swap_remove_instance(index);
     }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T {
+        if self.read().has_override(index) {
+            panic!("RwOverlay overrides are behind a lock and can't be borrowed; use get_entity_cow");
+        }
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity_cow(&self, index: usize, proto_idx: usize) -> Cow<'_, T> {
+        let guard = self.read();
+        if guard.has_override(index) {
+            Cow::Owned(guard.get(index, proto_idx).clone())
+        } else {
+            Cow::Borrowed(&self.dense_data[proto_idx])
+        }
+    }
+
+    fn override_state(&self, index: usize) -> Option<bool> {
+        Some(self.read().has_override(index))
+    }
+
+    fn pin_entity(&mut self, index: usize, proto_idx: usize) {
+        let value = self.dense_data[proto_idx].clone();
+        let overrides = self.overrides_mut();
+        if !overrides.has_override(index) {
+            overrides.set(index, value);
+        }
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self.overrides_mut().set(index, value);
+    }
+
+    fn clear_entity_override(&mut self, index: usize) {
+        self.overrides_mut().clear_override(index);
+    }
+
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.overrides_mut().clone_instance(src_idx);
+    }
+
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        self.dense_data.extend_from_slice(data);
+    }
+
+    fn override_count(&self) -> usize {
+        self.read().overrides.override_count()
+    }
+
+    fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        self.overrides_mut().clear_overrides_for_prototype(proto_id, entity_proto_ids)
+    }
+
+    fn get_as_json(&self, idx: usize, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        // Overrides can't be borrowed out of the lock (see `get_entity`), so serialize under it.
+        serde_json::to_value(self.read().get(idx, proto_idx)).expect("failed to serialize entity field")
+    }
+
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn swap_prototype_copies(&mut self, a: usize, b: usize) {
+        self.dense_data.swap(a, b);
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn transform_prototype_copies(&mut self, f: &dyn Fn(&T) -> T) {
+        for value in &mut self.dense_data {
+            *value = f(value);
+        }
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.instances_len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.prototypes_len())
+    }
+
+    fn row_capacity(&self) -> usize {
+        self.dense_data.capacity()
+    }
+
+    fn is_overlay() -> bool {
+        true
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&self.dense_data[proto_idx]).expect("failed to serialize prototype field")
+    }
 }
 
-// Honestly its kind of just fat so we can use it in both tables to simplify our shit
-// Can be used for - sparse data , defaults, copy on write data
-#[derive(Clone)]
-pub struct Overlay<T> {
+/// Like `Overlay<T>`, but prototype data is borrowed (`&'a [T]`) instead of owned.
+///
+/// Meant for prototype data compiled into the binary (a `static`/`const` table), so it's
+/// never copied into a `Vec`. That data is authoritative: when used in a SoA, JSON and slice
+/// loads (`push_json`, `push_prototypes_from_slice`) ignore this field, and the borrowed slice
+/// must already be in prototype_id order.
+///
+/// To build a prototypes table, set this field to `BorrowedOverlay::new(&TABLE)` on a default
+/// table, then load the other fields' prototypes in the same order. Debug builds panic if more
+/// prototypes are loaded than the slice holds.
+pub struct BorrowedOverlay<'a, T> {
     /// Prototype/template data (indexed by `prototype_id`).
-    pub dense_data: Vec<T>,
-
-    /// Per-instance overrides (keyed by `instance_id`).
-    pub sparse_data: HashMap<usize, T>,
+    pub dense_data: &'a [T],
 
-    /// Bitmask for which instances have overrides in `sparse_data`.
-    /// One bit per instance: 1 => present in `sparse_data`, 0 => use prototype fallback.
-    pub presence: Vec<u64>,
+    /// Per-instance overrides and presence bits (its own `dense_data` is unused).
+    overrides: Overlay<T>,
 
-    /// Logical number of instances being tracked by this overlay.
-    instance_len: usize,
+    /// Prototype rows loaded into the table so far (each one ignored by this field), checked
+    /// against the slice length.
+    loaded_rows: usize,
 }
 
-impl<T> Default for Overlay<T> {
+impl<T> Default for BorrowedOverlay<'_, T> {
     fn default() -> Self {
         Self {
-            dense_data: Vec::new(),
-            sparse_data: HashMap::new(),
-            presence: Vec::new(),
-            instance_len: 0,
+            dense_data: &[],
+            overrides: Overlay::default(),
+            loaded_rows: 0,
         }
     }
 }
 
-impl<T> Overlay<T>
+impl<'a, T> BorrowedOverlay<'a, T>
 where
     T: Clone,
 {
-    #[inline]
-    fn word_bit(instance_id: usize) -> (usize, u64) {
-        let word = instance_id >> 6;
-        let bit = instance_id & 63;
-        (word, 1u64 << bit)
+    pub fn new(dense_data: &'a [T]) -> Self {
+        Self {
+            dense_data,
+            overrides: Overlay::default(),
+            loaded_rows: 0,
+        }
     }
 
-    #[inline]
-    fn ensure_presence_capacity(&mut self, instance_id: usize) {
-        let (word, _) = Self::word_bit(instance_id);
-        if self.presence.len() <= word {
-            self.presence.resize(word + 1, 0);
-        }
+    /// Counts `n` prototype rows loaded into the table, which the slice must already hold.
+    fn note_loaded_rows(&mut self, n: usize) {
+        self.loaded_rows += n;
+        debug_assert!(
+            self.loaded_rows <= self.dense_data.len(),
+            "BorrowedOverlay: {} prototype rows loaded but the borrowed slice holds {}",
+            self.loaded_rows,
+            self.dense_data.len()
+        );
     }
 
     /// Number of spawned instances represented by this overlay.
     pub fn instances_len(&self) -> usize {
-        self.instance_len
+        self.overrides.instances_len()
     }
 
-    /// Number of loaded prototypes/templates represented by this overlay.
+    /// Number of prototypes/templates represented by this overlay.
     pub fn prototypes_len(&self) -> usize {
         self.dense_data.len()
     }
 
     /// Adds a new instance slot (no override set).
     pub fn push_instance(&mut self) {
-        let id = self.instance_len;
-        self.instance_len += 1;
-        self.ensure_presence_capacity(id);
-        // bit defaults to 0 => no override
+        self.overrides.push_instance();
     }
 
     /// Returns true if this instance has an override.
     pub fn has_override(&self, instance_id: usize) -> bool {
-        if instance_id >= self.instance_len {
-            return false;
-        }
-        let (word, mask) = Self::word_bit(instance_id);
-        (self.presence.get(word).copied().unwrap_or(0) & mask) != 0
+        self.overrides.has_override(instance_id)
     }
 
     /// Clears an override for `instance_id`, if present.
     pub fn clear_override(&mut self, instance_id: usize) {
-        if instance_id >= self.instance_len {
-            return;
-        }
-        let (word, mask) = Self::word_bit(instance_id);
-        if let Some(w) = self.presence.get_mut(word) {
-            *w &= !mask;
-        }
-        self.sparse_data.remove(&instance_id);
+        self.overrides.clear_override(instance_id);
     }
 
-    /// Read with fallback to prototype/template data.
-    ///
-    /// Requires `prototype_id` to be known by the caller (stored as a normal field on the SoA).
-    pub fn get(&self, instance_id: usize, prototype_id: usize) -> &T { // maybe we can grab prototype id with a macro
+    /// Prototype value, borrowed for the full `'a` rather than from `self`.
+    pub fn get_prototype(&self, prototype_id: usize) -> &'a T {
+        &self.dense_data[prototype_id]
+    }
+
+    /// Read with fallback to the borrowed prototype/template data.
+    pub fn get(&self, instance_id: usize, prototype_id: usize) -> &T {
         if self.has_override(instance_id) {
             return self
+                .overrides
                 .sparse_data
                 .get(&instance_id)
-                .expect("Overlay presence bit set but sparse_data missing entry");
+                .expect("BorrowedOverlay presence bit set but sparse_data missing entry");
         }
-        &self.dense_data[prototype_id]
+        self.get_prototype(prototype_id)
     }
 
-    /// Write access with copy-on-write from the prototype/template.
+    /// Write access with copy-on-write from the borrowed prototype/template.
     pub fn get_mut(&mut self, instance_id: usize, prototype_id: usize) -> &mut T {
-        if instance_id >= self.instance_len {
-            panic!("Overlay get_mut out of bounds: {instance_id} >= {}", self.instance_len);
-        }
-
         if !self.has_override(instance_id) {
-            let base = self.dense_data[prototype_id].clone();
-            self.sparse_data.insert(instance_id, base);
-            let (word, mask) = Self::word_bit(instance_id);
-            self.ensure_presence_capacity(instance_id);
-            self.presence[word] |= mask;
+            self.overrides.set(instance_id, self.dense_data[prototype_id].clone());
         }
-
-        self.sparse_data
+        self.overrides
+            .sparse_data
             .get_mut(&instance_id)
-            .expect("Overlay write: sparse_data missing entry after insert")
+            .expect("BorrowedOverlay write: sparse_data missing entry after insert")
     }
 
     /// Sets an override value for `instance_id` (marks presence bit).
     pub fn set(&mut self, instance_id: usize, value: T) {
-        if instance_id >= self.instance_len {
-            panic!("Overlay set out of bounds: {instance_id} >= {}", self.instance_len);
-        }
-        self.sparse_data.insert(instance_id, value);
-        let (word, mask) = Self::word_bit(instance_id);
-        self.ensure_presence_capacity(instance_id);
-        self.presence[word] |= mask;
+        self.overrides.set(instance_id, value);
     }
 
     /// Swap-remove an instance slot, keeping O(1) semantics consistent with `Vec::swap_remove`.
-    ///
-    /// If the last instance had an override, it is moved into `index`.
     pub fn swap_remove_instance(&mut self, index: usize) {
-        if index >= self.instance_len {
-            panic!(
-                "Overlay swap_remove out of bounds: {index} >= {}",
-                self.instance_len
-            );
-        }
+        self.overrides.swap_remove_instance(index);
+    }
+}
 
-        let last = self.instance_len - 1;
+impl<T> Storage<T> for BorrowedOverlay<'_, T>
+where
+    T: Clone + Default + DeserializeOwned,
+{
+    fn init_from_prototypes(&mut self, prototypes: &Self) {
+        self.dense_data = prototypes.dense_data;
+        self.overrides = Overlay::default();
+    }
 
-        // Remove index override (if any).
+    fn push_json(&mut self, _json: &Value) {
+        // Prototype data is the borrowed slice; JSON for this field is ignored.
+        self.note_loaded_rows(1);
+    }
+
+    fn replace_json(&mut self, proto_idx: usize, _json: &Value) {
+        // Prototype data is the borrowed slice; JSON for this field is ignored.
+        debug_assert!(
+            proto_idx < self.dense_data.len(),
+            "BorrowedOverlay: prototype row {proto_idx} is past the borrowed slice ({} rows)",
+            self.dense_data.len()
+        );
+    }
+
+    fn push_from_prototype(&mut self, _source: &Self, _proto_idx: usize) {
+        self.push_instance();
+    }
+
+    fn push_default(&mut self) {
+        self.push_instance();
+        self.set(self.instances_len() - 1, T::default());
+    }
+
+    fn swap_remove(&mut self, index: usize) {
+        self.swap_remove_instance(index);
+    }
+
+    fn prototype(&self, proto_idx: usize) -> &T {
+        &self.dense_data[proto_idx]
+    }
+
+    fn get_entity(&self, index: usize, proto_idx: usize) -> &T {
+        self.get(index, proto_idx)
+    }
+
+    fn override_state(&self, index: usize) -> Option<bool> {
+        Some(self.has_override(index))
+    }
+
+    fn pin_entity(&mut self, index: usize, proto_idx: usize) {
+        self.get_mut(index, proto_idx);
+    }
+
+    fn set_entity(&mut self, index: usize, value: T) {
+        self.set(index, value);
+    }
+
+    fn clear_entity_override(&mut self, index: usize) {
         self.clear_override(index);
+    }
 
-        if index != last {
-            let last_has = self.has_override(last);
+    fn clone_entity(&mut self, src_idx: usize)
+    where
+        T: Clone,
+    {
+        self.overrides.clone_instance(src_idx);
+    }
 
-            if last_has {
-                if let Some(v) = self.sparse_data.remove(&last) {
-                    self.sparse_data.insert(index, v);
-                } else {
-                    panic!("Overlay presence bit set for last but sparse_data missing entry");
-                }
-            }
+    fn push_prototypes_from_slice(&mut self, data: &[T])
+    where
+        T: Clone,
+    {
+        // Prototype data is the borrowed slice; it can't be extended.
+        self.note_loaded_rows(data.len());
+    }
 
-            // Copy last presence bit to index.
-            let (iw, im) = Self::word_bit(index);
-            let (lw, lm) = Self::word_bit(last);
-            self.ensure_presence_capacity(index);
-            self.ensure_presence_capacity(last);
+    fn override_count(&self) -> usize {
+        self.overrides.override_count()
+    }
 
-            if last_has {
-                self.presence[iw] |= im;
-            } else {
-                self.presence[iw] &= !im;
-            }
+    fn clear_overrides_for_prototype(&mut self, proto_id: usize, entity_proto_ids: &[usize]) -> usize {
+        self.overrides.clear_overrides_for_prototype(proto_id, entity_proto_ids)
+    }
 
-            // Clear last bit.
-            self.presence[lw] &= !lm;
-        } else {
-            // Clear last bit (already cleared by clear_override(index)).
-            let (lw, lm) = Self::word_bit(last);
-            if let Some(w) = self.presence.get_mut(lw) {
-                *w &= !lm;
-            }
-        }
+    fn swap_prototypes(&mut self, a: usize, b: usize) {
+        panic!("BorrowedOverlay can't swap prototypes {a} and {b}: prototype data is borrowed");
+    }
 
-        self.instance_len -= 1;
+    fn swap_prototype_copies(&mut self, a: usize, b: usize) {
+        panic!("BorrowedOverlay can't swap prototypes {a} and {b}: prototype data is borrowed");
+    }
+
+    fn transform_prototypes(&mut self, f: &dyn Fn(&T) -> T) {
+        let _ = f;
+        panic!("BorrowedOverlay prototypes are borrowed and can't be transformed");
+    }
+
+    fn entity_count(&self) -> Option<usize> {
+        Some(self.instances_len())
+    }
+
+    fn prototype_row_count(&self) -> Option<usize> {
+        Some(self.prototypes_len())
     }
-}
\ No newline at end of file
+
+    fn row_capacity(&self) -> usize {
+        self.dense_data.len()
+    }
+
+    fn is_overlay() -> bool {
+        true
+    }
+
+    fn export_json(&self, proto_idx: usize) -> Value
+    where
+        T: Serialize,
+    {
+        serde_json::to_value(&self.dense_data[proto_idx]).expect("failed to serialize prototype field")
+    }
+}
@@ -1,13 +1,14 @@
-use serde::Deserialize;
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use grug_soa::Overlay;
 
-#[derive(Default, Clone, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Foo {
     foo_field: String,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Bar {
     bar_field: i32,
 }
@@ -18,13 +19,13 @@ impl Default for Bar {
     }
 }
 
-#[derive(Clone, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
 pub struct Baz {
     a: String,
     inner: BazInner,
 }
 
-#[derive(Default, Clone, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
 pub struct BazInner {
     b: String,
     c: f32,
@@ -39,11 +40,13 @@ pub struct MySoA {
     num: Vec<i32>,
     name: Vec<String>,
     really_long_string: Overlay<String>,
+    name_index: HashMap<String, usize>,
+    #[allow(dead_code)]
     soa_ignored_field: String,
 }
 
-grug_soa::impl_load_prototype!(MySoA { prototype_id: usize, foo: Foo, bar: Bar, baz: Baz, num: i32, name: String, really_long_string: String });
-// nb can get random crashes if you forget a field here
+grug_soa::impl_load_prototype!(MySoA { prototype_id: usize, foo: Foo, bar: Bar, baz: Baz, num: i32, name: String [index_by], really_long_string: String } unregistered { name_index, soa_ignored_field });
+// nb can get random crashes if you forget a field here (unless you list unregistered fields, then it's a compile error)
 // and also the compile errors are in random places lol
 
 // you can have fields you choose not to register also (if other fields belonged in it)
@@ -96,6 +99,17 @@ fn main() {
     assert!((prototype_soa.baz[1].inner.c - 420.69).abs() < 1e-6);
     assert_eq!(prototype_soa.num[1], 696969);
     assert_eq!(prototype_soa.name[1], "grugname2");
+    assert_eq!(prototype_soa.prototype_by_name("grugname2"), Some(1));
+
+    // reorder prototypes; the name index follows
+    prototype_soa.swap_prototypes(0, 1);
+    assert_eq!(prototype_soa.prototype_by_name("grugname2"), Some(0));
+    prototype_soa.swap_prototypes(0, 1);
+
+    // designer data round-trips through json
+    let mut reloaded_soa = MySoA::default();
+    reloaded_soa.load_prototype_batch(prototype_soa.to_json_array());
+    assert_eq!(reloaded_soa.to_json_array(), prototype_soa.to_json_array());
 
 
     // runtime - no deserialization happening
@@ -120,4 +134,167 @@ fn main() {
         println!("name: {}", runtime_soa.name[i]);
         println!("really long string: {}", runtime_soa.really_long_string.get(i, runtime_soa.prototype_id[i])); // maybe this could be improved with macro magic
     }
-}
\ No newline at end of file
+
+    runtime_soa.debug_print_entity(0);
+    assert_eq!(runtime_soa.iter_field(|soa| &soa.really_long_string).count(), runtime_soa.len());
+    assert_eq!(runtime_soa.count_entities_with("num", |v| v.as_i64() > Some(1337)), 2);
+    assert_eq!(runtime_soa.count_entities_where(|soa| &soa.num, |n| *n > 1337), 2);
+    assert!(runtime_soa.entity_equals(0, 1));
+    assert!(runtime_soa.entities_equal_to_prototype(2, &prototype_soa, 0));
+
+    // fork entity 0, overrides and all
+    runtime_soa.really_long_string.set(0, "forked".to_string());
+    let forked = runtime_soa.clone_entity(0);
+    assert!(runtime_soa.entity_equals(0, forked));
+    assert_eq!(runtime_soa.diff_entity_from_prototype(forked, &prototype_soa, 1), ["really_long_string"]);
+    assert_eq!(runtime_soa.total_override_count(), 2);
+    assert_eq!(runtime_soa.override_count_for_field("num"), 0);
+
+    // promote entity 2 to prototype 1; the overlay field follows its new prototype
+    runtime_soa.migrate_prototype(&prototype_soa, 2, 1);
+    assert_eq!(runtime_soa.really_long_string.get(2, runtime_soa.prototype_id[2]), &prototype_soa.really_long_string.dense_data[1]);
+
+    // spawn with only num copied from the prototype
+    runtime_soa.partial_spawn_entity(&prototype_soa, 1, &["num"]);
+    let partial = runtime_soa.len() - 1;
+    assert_eq!(runtime_soa.num[partial], prototype_soa.num[1]);
+    assert_eq!(runtime_soa.name[partial], "");
+    assert_eq!(runtime_soa.really_long_string.get(partial, runtime_soa.prototype_id[partial]), "");
+
+    // balance pass: double every prototype's num
+    let nums = prototype_soa.num.clone();
+    prototype_soa.scale_prototype_field("num", 2.0).unwrap();
+    assert!(prototype_soa.num.iter().zip(&nums).all(|(scaled, n)| *scaled == n * 2));
+
+    // systems: bump num on every entity, then only on prototype-1 entities
+    let before = runtime_soa.num.clone();
+    runtime_soa.apply_system(|soa, i| soa.num[i] += 1);
+    runtime_soa.filter_system(|soa, i| soa.prototype_id[i] == 1, |soa, i| soa.num[i] += 1);
+    for (i, n) in before.iter().enumerate() {
+        let bumps = if runtime_soa.prototype_id[i] == 1 { 2 } else { 1 };
+        assert_eq!(runtime_soa.num[i], n + bumps);
+    }
+
+    // entity 0 overrides really_long_string, entity 2 doesn't
+    let string_size = std::mem::size_of::<String>();
+    assert_eq!(runtime_soa.entity_memory_size(0), runtime_soa.entity_memory_size(2) + string_size);
+
+    let (foo_value, _, _, num, name, long_string) = prototype_soa.prototype_as_snapshot(0);
+    assert_eq!(foo_value, prototype_soa.foo[0]);
+    assert_eq!(num, prototype_soa.num[0]);
+    assert_eq!(name, prototype_soa.name[0]);
+    assert_eq!(long_string, prototype_soa.really_long_string.dense_data[0]);
+
+    let blank = MySoA::component_default_json();
+    assert_eq!(blank["bar"], json!({ "bar_field": 69 }));
+    assert_eq!(blank.as_object().unwrap().len(), MySoA::component_names().len());
+
+    // despawn everything but prototype-1 entities
+    let others = runtime_soa.prototype_id.iter().filter(|&&p| p != 1).count();
+    assert_eq!(runtime_soa.retain_entities(|i, soa| soa.prototype_id[i] == 1), others);
+    assert!(runtime_soa.prototype_id.iter().all(|&p| p == 1));
+
+    // save-file style loading: ids out of order, gaps filled with defaults
+    let mut saved = MySoA::default();
+    saved.load_prototype_with_id(json!({ "name": "third", "num": 3 }), 2);
+    saved.load_prototype_with_id(json!({ "name": "first", "num": 1 }), 0);
+    assert_eq!(saved.prototype_count(), 3);
+    assert_eq!(saved.num, [1, 0, 3]);
+    assert_eq!(saved.prototype_by_name("first"), Some(0));
+    assert_eq!(saved.prototype_by_name("third"), Some(2));
+
+    // trade one field between two entities of different prototypes
+    let mut traders = MySoA::new_from_prototypes(&prototype_soa);
+    traders.spawn_entity(&prototype_soa, 0);
+    traders.spawn_entity(&prototype_soa, 1);
+    traders.swap_field(0, 1, "really_long_string").unwrap();
+    traders.swap_field(0, 1, "num").unwrap();
+    assert_eq!(traders.really_long_string.get(0, 0), &prototype_soa.really_long_string.dense_data[1]);
+    assert_eq!(traders.really_long_string.get(1, 1), &prototype_soa.really_long_string.dense_data[0]);
+    assert_eq!(traders.num, [prototype_soa.num[1], prototype_soa.num[0]]);
+    assert!(traders.swap_field(0, 1, "nope").is_err());
+
+    // twelve-factor config: GRUG_NUM parses as JSON, GRUG_NAME falls back to a raw string
+    // SAFETY: single-threaded demo, nothing else reads the environment concurrently.
+    unsafe {
+        std::env::set_var("GRUG_NUM", "77");
+        std::env::set_var("GRUG_NAME", "from env");
+    }
+    let env_id = saved.load_prototype_from_env("GRUG");
+    assert_eq!(saved.num[env_id], 77);
+    assert_eq!(saved.name[env_id], "from env");
+    assert_eq!(saved.bar[env_id], Bar::default());
+
+    assert!(prototype_soa.len_consistent());
+    assert!(runtime_soa.len_consistent());
+    runtime_soa.num.push(0); // a stray push outside the macro's methods
+    assert!(!runtime_soa.len_consistent());
+    runtime_soa.num.pop();
+
+    let table = runtime_soa.to_tabular_string();
+    println!("{table}");
+    assert!(table.starts_with("prototype_id | foo"));
+    assert_eq!(table.lines().count(), runtime_soa.len() + 1);
+
+    assert_eq!(MySoA::field_size_of("num"), std::mem::size_of::<i32>());
+    assert_eq!(MySoA::field_size_of("nope"), 0);
+    let row_size: usize = MySoA::component_names().iter().map(|f| MySoA::field_size_of(f)).sum();
+    assert_eq!(MySoA::total_prototype_memory(3), row_size * 3);
+
+    // save two entities, scribble over them, then restore
+    let snapshot = runtime_soa.snapshot_entities(&[0, 1]);
+    assert_eq!(snapshot[0]["fields"]["really_long_string"], "forked");
+    runtime_soa.num[0] = -1;
+    runtime_soa.really_long_string.set(1, "scribble".to_string());
+    let saved_num = snapshot[0]["fields"]["num"].as_i64().unwrap() as i32;
+    runtime_soa.restore_entities(snapshot);
+    assert_eq!(runtime_soa.num[0], saved_num);
+    assert!(!runtime_soa.really_long_string.has_override(1));
+
+    // multi-field queries
+    let forked_one = runtime_soa.find_entity(|i, soa| soa.num[i] == saved_num && soa.really_long_string.has_override(i));
+    assert_eq!(forked_one, Some(0));
+    assert_eq!(runtime_soa.find_entities(|i, soa| soa.prototype_id[i] == 1).len(), runtime_soa.len());
+    assert!(!runtime_soa.any_entity(|i, soa| soa.prototype_id[i] == 0));
+
+    let summary = runtime_soa.capacity_summary();
+    assert_eq!(summary.len(), MySoA::component_names().len() + 1);
+    assert_eq!(summary[0], ("prototype_id", runtime_soa.prototype_id.capacity(), runtime_soa.len()));
+    assert!(summary.iter().all(|&(_, _, len)| len == runtime_soa.len()));
+
+    // stream out entities 2 and 0, in that order
+    let chunk = runtime_soa.clone_partial(&[2, 0]);
+    assert_eq!(chunk.len(), 2);
+    assert!(chunk.len_consistent());
+    assert_eq!(chunk.prototype_id, [runtime_soa.prototype_id[2], runtime_soa.prototype_id[0]]);
+    assert_eq!(chunk.num, [runtime_soa.num[2], runtime_soa.num[0]]);
+    assert_eq!(chunk.really_long_string.get(1, chunk.prototype_id[1]), "forked");
+    assert!(chunk.really_long_string.has_override(0));
+
+    assert!(MySoA::field_type_is_overlay("really_long_string"));
+    assert!(!MySoA::field_type_is_overlay("num"));
+    assert!(!MySoA::field_type_is_overlay("nope"));
+
+    assert_eq!(runtime_soa.validate_prototype_ids(&prototype_soa), Ok(()));
+    let mut stale = runtime_soa.clone_partial(&[0]);
+    stale.prototype_id[0] = 7;
+    assert_eq!(stale.validate_prototype_ids(&prototype_soa), Err(vec![(0, 7)]));
+
+    assert!(prototype_soa.prototype_ids_are_unique());
+    assert!(saved.prototype_ids_are_unique());
+    assert!(!runtime_soa.prototype_ids_are_unique()); // several entities per prototype
+
+    assert!(runtime_soa.field_lens_equal());
+    assert!(prototype_soa.field_lens_equal());
+    stale.name.push("stray".to_string());
+    assert!(!stale.field_lens_equal());
+
+    let forked_count = runtime_soa.instance_count_matching("really_long_string", &json!("forked"));
+    assert!(forked_count > 0);
+    assert_eq!(forked_count, runtime_soa.iter_field(|soa| &soa.really_long_string).filter(|s| s.as_str() == "forked").count());
+    assert_eq!(runtime_soa.instance_count_matching("num", &json!("not a number")), 0);
+    assert_eq!(runtime_soa.instance_count_matching("nope", &json!(1)), 0);
+
+    let doubled = runtime_soa.map_field_values("num", |v| json!(v.as_i64().unwrap() * 2));
+    assert_eq!(doubled, runtime_soa.num.iter().map(|n| json!(n * 2)).collect::<Vec<_>>());
+}
@@ -0,0 +1,129 @@
+use grug_soa::AtomicOverlay;
+use serde_json::json;
+
+const PROTO: u32 = 1000;
+
+fn overlay_with(n: usize) -> AtomicOverlay<u32> {
+    let mut overlay = AtomicOverlay::default();
+    overlay.dense_data.push(PROTO);
+    for _ in 0..n {
+        overlay.push_instance();
+    }
+    overlay
+}
+
+#[test]
+fn set_get_clear() {
+    let overlay = overlay_with(3);
+    assert_eq!(overlay.get(1, 0), PROTO);
+
+    overlay.set(1, 7);
+    assert!(overlay.has_override(1));
+    assert_eq!(overlay.get(1, 0), 7);
+    assert_eq!(overlay.get(0, 0), PROTO);
+
+    overlay.clear_override(1);
+    assert!(!overlay.has_override(1));
+    assert_eq!(overlay.get(1, 0), PROTO);
+}
+
+#[test]
+fn swap_remove_across_word_boundary() {
+    let mut overlay = overlay_with(65);
+    overlay.set(64, 64);
+    overlay.set(10, 10);
+
+    overlay.swap_remove_instance(10);
+    assert_eq!(overlay.instances_len(), 64);
+    assert_eq!(overlay.get(10, 0), 64);
+    assert!(!overlay.has_override(64));
+
+    overlay.swap_remove_instance(63);
+    assert_eq!(overlay.get(10, 0), 64);
+    assert_eq!(overlay.instances_len(), 63);
+}
+
+#[test]
+fn concurrent_reads_and_sets() {
+    let overlay = overlay_with(256);
+    std::thread::scope(|scope| {
+        for t in 0..4 {
+            let overlay = &overlay;
+            scope.spawn(move || {
+                for i in (t..256).step_by(4) {
+                    overlay.set(i, i as u32);
+                    assert_eq!(overlay.get(i, 0), i as u32);
+                }
+            });
+        }
+        scope.spawn(|| {
+            for i in 0..256 {
+                let v = overlay.get(i, 0);
+                assert!(v == PROTO || v == i as u32);
+            }
+        });
+    });
+    for i in 0..256 {
+        assert_eq!(overlay.get(i, 0), i as u32);
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    prototype_id: Vec<usize>,
+    hits: AtomicOverlay<u32>,
+    name: Vec<String>,
+}
+
+grug_soa::impl_load_prototype!(Counters { prototype_id: usize, hits: u32, name: String });
+
+#[test]
+fn generated_readers_see_locked_overrides() {
+    let mut prototypes = Counters::default();
+    prototypes.load_prototype(json!({ "hits": 1, "name": "a" }));
+
+    let mut live = Counters::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 0);
+    live.spawn_entity(&prototypes, 0);
+    live.hits.set(1, 5);
+
+    assert!(!live.entity_equals(0, 1));
+    assert_eq!(live.diff_entities(0, 1), ["hits"]);
+    assert_ne!(live.entity_checksum(0), live.entity_checksum(1));
+    assert_eq!(live.count_entities_with("hits", |v| v == &json!(5)), 1);
+    assert_eq!(live.iter_field(|soa| &soa.hits).map(|v| *v).collect::<Vec<_>>(), [1, 5]);
+    assert!(live.to_tabular_string().contains("5*"));
+    assert_eq!(live.entity_to_json(1)["hits"], 5);
+
+    let copy = live.clone_partial(&[1]);
+    assert_eq!(copy.hits.get(0, 0), 5);
+
+    live.swap_field(0, 1, "hits").unwrap();
+    assert_eq!(live.hits.get(0, 0), 5);
+    assert_eq!(live.hits.get(1, 0), 1);
+}
+
+#[test]
+fn racing_set_and_clear_leave_bit_and_map_in_step() {
+    let overlay = overlay_with(8);
+    std::thread::scope(|scope| {
+        for t in 0..2 {
+            let overlay = &overlay;
+            scope.spawn(move || {
+                for n in 0..2_000 {
+                    let i = n % 8;
+                    if (n + t) % 2 == 0 {
+                        overlay.set(i, n as u32);
+                    } else {
+                        overlay.clear_override(i);
+                    }
+                }
+            });
+        }
+    });
+    let present = (0..8).filter(|&i| overlay.has_override(i)).count();
+    assert_eq!(present, overlay.sparse_data.read().unwrap().len());
+    for i in 0..8 {
+        assert_eq!(overlay.has_override(i), overlay.sparse_data.read().unwrap().contains_key(&i));
+    }
+}
@@ -0,0 +1,79 @@
+use grug_soa::BorrowedOverlay;
+use serde_json::json;
+
+static SPEEDS: [u32; 3] = [10, 20, 30];
+
+#[derive(Default)]
+struct Units {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    speed: BorrowedOverlay<'static, u32>,
+}
+
+grug_soa::impl_load_prototype!(Units { prototype_id: usize, hp: u32, speed: u32 });
+
+fn prototypes() -> Units {
+    let mut prototypes = Units { speed: BorrowedOverlay::new(&SPEEDS), ..Default::default() };
+    for hp in [1, 2, 3] {
+        // `speed` comes from the static table; JSON for it is ignored.
+        prototypes.load_prototype(json!({ "hp": hp, "speed": 999 }));
+    }
+    prototypes
+}
+
+#[test]
+fn spawned_entities_read_the_static_table() {
+    let prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 2);
+    live.spawn_entity(&prototypes, 0);
+
+    assert_eq!(live.hp, [3, 1]);
+    assert_eq!(*live.speed.get(0, live.prototype_id[0]), 30);
+    assert_eq!(*live.speed.get(1, live.prototype_id[1]), 10);
+    // borrowed, not copied
+    assert!(std::ptr::eq(live.speed.dense_data, &SPEEDS[..]));
+    assert!(std::ptr::eq(live.speed.get_prototype(1), &SPEEDS[1]));
+}
+
+#[test]
+fn get_mut_copies_on_write() {
+    let prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 1);
+    live.spawn_entity(&prototypes, 1);
+
+    *live.speed.get_mut(0, 1) += 5;
+    assert_eq!(*live.speed.get(0, 1), 25);
+    assert!(live.speed.has_override(0));
+    assert_eq!(*live.speed.get(1, 1), 20);
+    assert!(!live.speed.has_override(1));
+    assert_eq!(SPEEDS[1], 20);
+
+    live.speed.clear_override(0);
+    assert_eq!(*live.speed.get(0, 1), 20);
+}
+
+#[test]
+fn swap_remove_keeps_overrides_with_their_entity() {
+    let prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+    for proto in [0, 1, 2] {
+        live.spawn_entity(&prototypes, proto);
+    }
+    live.speed.set(2, 7);
+
+    live.swap_remove(0);
+    assert_eq!(live.prototype_id, [2, 1]);
+    assert_eq!(live.speed.instances_len(), 2);
+    assert_eq!(*live.speed.get(0, live.prototype_id[0]), 7);
+    assert_eq!(*live.speed.get(1, live.prototype_id[1]), 20);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "borrowed slice holds 3")]
+fn loading_more_prototypes_than_the_slice_panics() {
+    let mut prototypes = prototypes();
+    prototypes.load_prototype(json!({ "hp": 4 }));
+}
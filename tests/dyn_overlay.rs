@@ -0,0 +1,53 @@
+use grug_soa::DynOverlay;
+
+trait Component: Send + Sync {
+    fn describe(&self) -> String;
+    fn boxed_clone(&self) -> Box<dyn Component>;
+    fn bump(&mut self);
+}
+
+#[derive(Clone)]
+struct Health(u32);
+
+impl Component for Health {
+    fn describe(&self) -> String {
+        format!("health {}", self.0)
+    }
+    fn boxed_clone(&self) -> Box<dyn Component> {
+        Box::new(self.clone())
+    }
+    fn bump(&mut self) {
+        self.0 += 1;
+    }
+}
+
+#[derive(Clone)]
+struct Label(&'static str);
+
+impl Component for Label {
+    fn describe(&self) -> String {
+        format!("label {}", self.0)
+    }
+    fn boxed_clone(&self) -> Box<dyn Component> {
+        Box::new(self.clone())
+    }
+    fn bump(&mut self) {}
+}
+
+#[test]
+fn dyn_overlay_copies_on_write_through_clone_fn() {
+    let mut overlay = DynOverlay::<dyn Component>::new(|c| c.boxed_clone());
+    let health = overlay.push_prototype(Box::new(Health(10)));
+    let label = overlay.push_prototype(Box::new(Label("tree")));
+    overlay.push_instance();
+    overlay.push_instance();
+
+    overlay.get_mut(0, health).bump();
+    assert_eq!(overlay.get(0, health).describe(), "health 11");
+    assert_eq!(overlay.get(1, health).describe(), "health 10");
+
+    overlay.set(1, Box::new(Label("rock")));
+    overlay.swap_remove_instance(0);
+    assert_eq!(overlay.get(0, label).describe(), "label rock");
+    assert_eq!(overlay.instances_len(), 1);
+}
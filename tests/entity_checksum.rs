@@ -0,0 +1,34 @@
+use grug_soa::Overlay;
+use serde_json::json;
+
+#[derive(Default)]
+struct Units {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    name: Overlay<String>,
+}
+
+grug_soa::impl_load_prototype!(Units { prototype_id: usize, hp: u32, name: String });
+
+#[test]
+fn checksum_tracks_effective_values() {
+    let mut prototypes = Units::default();
+    prototypes.load_prototype(json!({ "hp": 10, "name": "grunt" }));
+    prototypes.load_prototype(json!({ "hp": 10, "name": "archer" }));
+
+    let mut live = Units::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 0);
+    live.spawn_entity(&prototypes, 0);
+    live.spawn_entity(&prototypes, 1);
+
+    let last_frame: Vec<u64> = (0..live.len()).map(|i| live.entity_checksum(i)).collect();
+    assert_eq!(last_frame[0], last_frame[1]);
+    assert_ne!(last_frame[0], last_frame[2]);
+
+    live.hp[1] = 3;
+    live.name.set(2, "archer".to_string()); // same effective value as the prototype
+    let changed: Vec<usize> = (0..live.len())
+        .filter(|&i| live.entity_checksum(i) != last_frame[i])
+        .collect();
+    assert_eq!(changed, [1]);
+}
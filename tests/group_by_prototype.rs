@@ -0,0 +1,51 @@
+use grug_soa::group_by_prototype_ids;
+use serde_json::json;
+
+#[derive(Default)]
+struct Mobs {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+}
+
+grug_soa::impl_load_prototype!(Mobs { prototype_id: usize, hp: u32 });
+
+fn tables(spawn: &[usize]) -> (Mobs, Mobs) {
+    let mut prototypes = Mobs::default();
+    for hp in [5, 50, 500, 5000] {
+        prototypes.load_prototype(json!({ "hp": hp }));
+    }
+    let mut live = Mobs::new_from_prototypes(&prototypes);
+    for &proto in spawn {
+        live.spawn_entity(&prototypes, proto);
+    }
+    (prototypes, live)
+}
+
+#[test]
+fn one_group_per_prototype() {
+    // No entities of prototypes 0 or 3: the result still has a group for each.
+    let (prototypes, live) = tables(&[2, 1, 2]);
+    assert_eq!(live.group_by_prototype(&prototypes), [vec![], vec![1], vec![0, 2], vec![]]);
+}
+
+#[test]
+fn empty_table_has_empty_groups() {
+    let (prototypes, live) = tables(&[]);
+    assert_eq!(live.group_by_prototype(&prototypes), vec![Vec::<usize>::new(); 4]);
+}
+
+#[test]
+fn lazy_groups_match() {
+    let (prototypes, live) = tables(&[3, 0, 3]);
+    let lazy = live.group_by_prototype_lazy(&prototypes);
+    assert_eq!(lazy.groups(), live.group_by_prototype(&prototypes));
+    assert_eq!(lazy.get(3), [0, 2]);
+    assert_eq!(lazy.get(1), [] as [usize; 0]);
+    assert_eq!(lazy.get(9), [] as [usize; 0]);
+}
+
+#[test]
+#[should_panic(expected = "entity 1 has prototype 4 out of range (4 prototypes)")]
+fn unknown_prototype_id_panics() {
+    group_by_prototype_ids(&[0, 4], 4);
+}
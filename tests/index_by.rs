@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use serde_json::json;
+
+#[derive(Default)]
+struct Mobs {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    name: Vec<String>,
+    name_index: HashMap<String, usize>,
+}
+
+grug_soa::impl_load_prototype!(Mobs { prototype_id: usize, hp: u32, name: String [index_by] } unregistered { name_index });
+
+fn prototypes(names: &[&str]) -> Mobs {
+    let mut prototypes = Mobs::default();
+    for (hp, name) in names.iter().enumerate() {
+        prototypes.load_prototype(json!({ "hp": hp, "name": name }));
+    }
+    prototypes
+}
+
+/// Every indexed name points at a prototype that has it.
+fn check_index(mobs: &Mobs) {
+    for (name, &proto) in &mobs.name_index {
+        assert_eq!(&mobs.name[proto], name, "index entry {name:?} -> {proto}");
+    }
+}
+
+#[test]
+fn load_indexes_names() {
+    let mobs = prototypes(&["goblin", "orc"]);
+    assert_eq!(mobs.prototype_by_name("goblin"), Some(0));
+    assert_eq!(mobs.prototype_by_name("orc"), Some(1));
+    assert_eq!(mobs.prototype_by_name("elf"), None);
+    assert_eq!(mobs.name_index.len(), 2);
+}
+
+#[test]
+fn load_from_slices_indexes_names() {
+    let mut mobs = prototypes(&["goblin"]);
+    mobs.load_prototypes_from_slices(&[7, 8], &["orc".into(), "elf".into()]);
+    assert_eq!(mobs.prototype_by_name("orc"), Some(1));
+    assert_eq!(mobs.prototype_by_name("elf"), Some(2));
+    check_index(&mobs);
+}
+
+#[test]
+fn overwrite_renames_the_entry() {
+    let mut mobs = prototypes(&["goblin", "orc"]);
+    mobs.load_prototype_with_id(json!({ "hp": 9, "name": "troll" }), 0);
+
+    assert_eq!(mobs.prototype_by_name("goblin"), None);
+    assert_eq!(mobs.prototype_by_name("troll"), Some(0));
+    assert_eq!(mobs.prototype_by_name("orc"), Some(1));
+    assert_eq!(mobs.name_index.len(), 2);
+    check_index(&mobs);
+}
+
+#[test]
+fn overwrite_with_the_same_name_keeps_the_entry() {
+    let mut mobs = prototypes(&["goblin", "orc"]);
+    mobs.load_prototype_with_id(json!({ "hp": 9, "name": "orc" }), 1);
+    assert_eq!(mobs.prototype_by_name("orc"), Some(1));
+    assert_eq!(mobs.hp, [0, 9]);
+    check_index(&mobs);
+}
+
+#[test]
+fn swap_remaps_the_index() {
+    let mut mobs = prototypes(&["goblin", "orc", "elf"]);
+    mobs.swap_prototypes(0, 2);
+
+    assert_eq!(mobs.name, ["elf", "orc", "goblin"]);
+    assert_eq!(mobs.prototype_by_name("goblin"), Some(2));
+    assert_eq!(mobs.prototype_by_name("elf"), Some(0));
+    assert_eq!(mobs.prototype_by_name("orc"), Some(1));
+    check_index(&mobs);
+}
+
+#[test]
+fn duplicate_name_points_at_the_newest_load() {
+    let mut mobs = prototypes(&["orc", "goblin", "orc"]);
+    assert_eq!(mobs.prototype_by_name("orc"), Some(2));
+
+    // Reloading the newest under another name hands the entry back to the older orc.
+    mobs.load_prototype_with_id(json!({ "name": "orc chief" }), 2);
+    assert_eq!(mobs.prototype_by_name("orc"), Some(0));
+    assert_eq!(mobs.prototype_by_name("orc chief"), Some(2));
+
+    // Reloading the older one again is an ordinary load: it becomes the newest orc.
+    mobs.load_prototype_with_id(json!({ "name": "orc" }), 1);
+    assert_eq!(mobs.prototype_by_name("orc"), Some(1));
+    mobs.load_prototype_with_id(json!({ "name": "goblin" }), 1);
+    assert_eq!(mobs.prototype_by_name("orc"), Some(0));
+    check_index(&mobs);
+}
+
+#[test]
+fn gap_filled_defaults_share_the_empty_name() {
+    let mut mobs = Mobs::default();
+    mobs.load_prototype_with_id(json!({ "name": "boss" }), 3);
+    assert_eq!(mobs.prototype_by_name(""), Some(2));
+    assert_eq!(mobs.prototype_by_name("boss"), Some(3));
+
+    mobs.load_prototype_with_id(json!({ "name": "imp" }), 2);
+    assert_eq!(mobs.prototype_by_name(""), Some(1));
+    mobs.load_prototype_with_id(json!({ "name": "bat" }), 0);
+    mobs.load_prototype_with_id(json!({ "name": "rat" }), 1);
+    assert_eq!(mobs.prototype_by_name(""), None);
+    assert_eq!(mobs.name_index.len(), 4);
+    check_index(&mobs);
+}
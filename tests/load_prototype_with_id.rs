@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use grug_soa::{Overlay, UnknownFieldError};
+use serde_json::json;
+
+#[derive(Default)]
+struct Mobs {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    name: Vec<String>,
+    title: Overlay<String>,
+    name_index: HashMap<String, usize>,
+}
+
+grug_soa::impl_load_prototype!(Mobs { prototype_id: usize, hp: u32, name: String [index_by], title: String } unregistered { name_index });
+
+#[derive(Default)]
+struct StrictMobs {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+}
+
+grug_soa::impl_load_prototype!(#[soa(deny_unknown_fields)] StrictMobs { prototype_id: usize, hp: u32 });
+
+fn mobs() -> Mobs {
+    let mut mobs = Mobs::default();
+    mobs.load_prototype(json!({ "hp": 5, "name": "goblin", "title": "Sneaky" }));
+    mobs.load_prototype(json!({ "hp": 50, "name": "orc", "title": "Mighty" }));
+    mobs
+}
+
+#[test]
+fn id_equal_to_count_appends() {
+    let mut mobs = mobs();
+    mobs.load_prototype_with_id(json!({ "hp": 500, "name": "troll", "title": "Huge" }), 2);
+
+    assert_eq!(mobs.prototype_count(), 3);
+    assert_eq!(mobs.prototype_id, [0, 1, 2]);
+    assert_eq!(mobs.hp, [5, 50, 500]);
+    assert_eq!(mobs.title.dense_data, ["Sneaky", "Mighty", "Huge"]);
+    assert_eq!(mobs.prototype_by_name("troll"), Some(2));
+}
+
+#[test]
+fn id_past_count_fills_gaps_with_defaults() {
+    let mut mobs = mobs();
+    mobs.load_prototype_with_id(json!({ "hp": 500, "name": "troll", "title": "Huge" }), 4);
+
+    assert_eq!(mobs.prototype_count(), 5);
+    assert_eq!(mobs.prototype_id, [0, 1, 2, 3, 4]);
+    assert_eq!(mobs.hp, [5, 50, 0, 0, 500]);
+    assert_eq!(mobs.name, ["goblin", "orc", "", "", "troll"]);
+    assert_eq!(mobs.title.dense_data, ["Sneaky", "Mighty", "", "", "Huge"]);
+    assert!(mobs.len_consistent());
+    assert_eq!(mobs.prototype_by_name("troll"), Some(4));
+}
+
+#[test]
+fn id_below_count_overwrites_in_place() {
+    let mut mobs = mobs();
+    mobs.load_prototype_with_id(json!({ "hp": 6, "name": "hobgoblin" }), 0);
+
+    assert_eq!(mobs.prototype_count(), 2);
+    assert_eq!(mobs.prototype_id, [0, 1]);
+    assert_eq!(mobs.hp, [6, 50]);
+    assert_eq!(mobs.name, ["hobgoblin", "orc"]);
+    // missing fields are reset to their defaults, like a fresh load
+    assert_eq!(mobs.title.dense_data, ["", "Mighty"]);
+}
+
+#[test]
+fn overwrite_moves_the_name_index_entry() {
+    let mut mobs = mobs();
+    mobs.load_prototype_with_id(json!({ "name": "hobgoblin" }), 0);
+
+    assert_eq!(mobs.prototype_by_name("goblin"), None);
+    assert_eq!(mobs.prototype_by_name("hobgoblin"), Some(0));
+    assert_eq!(mobs.prototype_by_name("orc"), Some(1));
+    assert_eq!(mobs.name_index.len(), 2);
+}
+
+#[test]
+fn out_of_order_save_file_round_trips() {
+    let mut saved = Mobs::default();
+    for (id, name) in [(2, "troll"), (0, "goblin"), (1, "orc")] {
+        saved.load_prototype_with_id(json!({ "name": name }), id);
+    }
+    assert_eq!(saved.name, ["goblin", "orc", "troll"]);
+    for (id, name) in saved.name.iter().enumerate() {
+        assert_eq!(saved.prototype_by_name(name), Some(id));
+    }
+    assert_eq!(saved.name_index.len(), 3);
+}
+
+#[test]
+fn deny_unknown_fields_rejects_before_loading() {
+    let mut mobs = StrictMobs::default();
+    assert_eq!(
+        mobs.load_prototype_with_id(json!({ "hp": 1, "hpp": 2 }), 3),
+        Err(UnknownFieldError { field: "hpp".into() })
+    );
+    assert_eq!(mobs.prototype_count(), 0);
+
+    mobs.load_prototype_with_id(json!({ "hp": 1 }), 1).unwrap();
+    assert_eq!(mobs.hp, [0, 1]);
+}
@@ -0,0 +1,72 @@
+use grug_soa::Overlay;
+use serde_json::json;
+
+#[derive(Default)]
+struct Mobs {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    name: Overlay<String>,
+}
+
+grug_soa::impl_load_prototype!(Mobs { prototype_id: usize, hp: u32, name: String });
+
+fn tables() -> (Mobs, Mobs) {
+    let mut prototypes = Mobs::default();
+    prototypes.load_prototype(json!({ "hp": 5, "name": "goblin" }));
+    prototypes.load_prototype(json!({ "hp": 50, "name": "orc" }));
+    let mut live = Mobs::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 0);
+    live.spawn_entity(&prototypes, 0);
+    live.name.set(1, "Grub".into());
+    (prototypes, live)
+}
+
+#[test]
+fn migrate_follows_the_new_prototype() {
+    let (prototypes, mut live) = tables();
+    live.migrate_prototype(&prototypes, 0, 1);
+    live.migrate_prototype(&prototypes, 1, 1);
+
+    assert_eq!(live.prototype_id, [1, 1]);
+    assert_eq!(live.name.get(0, 1), "orc");
+    // overrides and dense fields keep their values
+    assert_eq!(live.name.get(1, 1), "Grub");
+    assert_eq!(live.hp, [5, 5]);
+}
+
+#[test]
+fn migrate_keep_values_pins_the_old_prototype_value() {
+    let (prototypes, mut live) = tables();
+    live.migrate_prototype_keep_values(&prototypes, 0, 1);
+    live.migrate_prototype_keep_values(&prototypes, 1, 1);
+
+    assert_eq!(live.prototype_id, [1, 1]);
+    assert_eq!(live.name.get(0, 1), "goblin");
+    assert!(live.name.has_override(0));
+    assert_eq!(live.name.get(1, 1), "Grub");
+}
+
+#[test]
+#[should_panic(expected = "prototype 2 out of range (2 prototypes)")]
+fn migrate_rejects_unknown_prototype() {
+    let (prototypes, mut live) = tables();
+    live.migrate_prototype(&prototypes, 0, 2);
+}
+
+#[test]
+#[should_panic(expected = "prototype 2 out of range (2 prototypes)")]
+fn migrate_keep_values_rejects_unknown_prototype() {
+    let (prototypes, mut live) = tables();
+    live.migrate_prototype_keep_values(&prototypes, 0, 2);
+}
+
+#[test]
+fn rejected_migration_leaves_the_entity_alone() {
+    let (prototypes, mut live) = tables();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        live.migrate_prototype_keep_values(&prototypes, 0, 7);
+    }));
+    assert!(result.is_err());
+    assert_eq!(live.prototype_id[0], 0);
+    assert!(!live.name.has_override(0));
+}
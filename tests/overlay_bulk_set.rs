@@ -0,0 +1,210 @@
+use grug_soa::Overlay;
+
+#[test]
+fn bulk_set_matches_individual_sets() {
+    let mut bulk = Overlay::default();
+    let mut single = Overlay::default();
+    for overlay in [&mut bulk, &mut single] {
+        overlay.dense_data.push(0u32);
+        for _ in 0..200 {
+            overlay.push_instance();
+        }
+    }
+
+    let updates = [(130, 1), (3, 2), (64, 3), (3, 4), (199, 5), (65, 6)];
+    bulk.bulk_set_from_iter(updates);
+    for (id, value) in updates {
+        single.set(id, value);
+    }
+
+    bulk.check_invariants();
+    assert_eq!(bulk.presence, single.presence);
+    assert_eq!(bulk.sparse_data, single.sparse_data);
+    assert_eq!(*bulk.get(3, 0), 4);
+}
+
+#[test]
+#[should_panic(expected = "bulk_set_from_iter out of bounds: 10 >= 4")]
+fn bulk_set_checks_every_id_first() {
+    let mut overlay: Overlay<u32> = [0].into_iter().collect();
+    for _ in 0..4 {
+        overlay.push_instance();
+    }
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        overlay.bulk_set_from_iter([(1, 1), (10, 2), (2, 3)]);
+    }));
+    assert_eq!(overlay.override_count(), 0);
+    overlay.check_invariants();
+    std::panic::resume_unwind(result.unwrap_err());
+}
+
+#[test]
+fn collect_and_extend_fill_prototypes() {
+    let mut overlay: Overlay<f32> = [1.0, 2.0].into_iter().collect();
+    overlay.extend([3.0]);
+    assert_eq!(overlay.dense_data, [1.0, 2.0, 3.0]);
+    assert_eq!(overlay.instances_len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "1 instance(s) use a removed prototype")]
+fn resize_prototypes_rejects_dangling_instances() {
+    let mut overlay: Overlay<u32> = [1, 2, 3].into_iter().collect();
+    overlay.resize_prototypes(5, 0, &[]);
+    assert_eq!(overlay.dense_data, [1, 2, 3, 0, 0]);
+
+    overlay.push_instance();
+    overlay.push_instance();
+    overlay.resize_prototypes(2, 0, &[0, 4]);
+}
+
+#[test]
+fn with_prototype_capacity_only_reserves_prototypes() {
+    let overlay = Overlay::<String>::with_prototype_capacity(32);
+    assert!(overlay.dense_data.capacity() >= 32);
+    assert!(overlay.dense_data.is_empty());
+    assert_eq!(overlay.instances_len(), 0);
+    assert_eq!(overlay.sparse_data.capacity(), 0);
+}
+
+#[test]
+fn take_prototype_only_when_unused() {
+    let mut overlay = Overlay::<u32>::default();
+    overlay.dense_data = vec![10, 20];
+    overlay.push_instance();
+    overlay.push_instance();
+
+    assert_eq!(overlay.remove_last_prototype_if_unused(&[0, 1]), None);
+    assert_eq!(overlay.prototypes_len(), 2);
+    assert_eq!(overlay.remove_last_prototype_if_unused(&[0, 0]), Some(20));
+    assert_eq!(overlay.prototypes_len(), 1);
+}
+
+#[test]
+fn take_prototype_pops_until_empty() {
+    let mut overlay = Overlay::<u32>::default();
+    overlay.dense_data = vec![10, 20];
+    assert_eq!(overlay.take_prototype(&[]), Some(20));
+    assert_eq!(overlay.take_prototype(&[]), Some(10));
+    assert_eq!(overlay.take_prototype(&[]), None);
+}
+
+#[test]
+#[should_panic(expected = "still used")]
+fn take_prototype_panics_when_used() {
+    let mut overlay = Overlay::<u32>::default();
+    overlay.dense_data = vec![10, 20];
+    overlay.push_instance();
+    overlay.take_prototype(&[1]);
+}
+
+#[test]
+fn merge_and_patch_prototype_tables() {
+    let mut base: Overlay<&str> = ["goblin", "orc"].into_iter().collect();
+    base.push_instance();
+    base.set(0, "named goblin");
+    let modded: Overlay<&str> = ["troll"].into_iter().collect();
+
+    let merged = base.merge_prototype_tables(&modded);
+    assert_eq!(merged.dense_data, ["goblin", "orc", "troll"]);
+    assert_eq!(merged.instances_len(), 0);
+
+    let patch: Overlay<&str> = ["big orc"].into_iter().collect();
+    base.apply_prototype_patch(&patch, &[1]);
+    assert_eq!(base.dense_data, ["goblin", "big orc"]);
+    assert_eq!(*base.get(0, 0), "named goblin");
+}
+
+#[test]
+fn storage_stats_reflect_internals() {
+    let mut overlay: Overlay<u32> = [1, 2, 3].into_iter().collect();
+    for i in 0..70 {
+        overlay.push_instance();
+        if i % 10 == 0 {
+            overlay.set(i, 0);
+        }
+    }
+
+    let stats = overlay.storage_stats();
+    assert_eq!(stats.prototype_count, 3);
+    assert_eq!(stats.instance_count, 70);
+    assert_eq!(stats.override_count, 7);
+    assert_eq!(stats.presence_words, 2);
+    assert!(stats.dense_capacity >= 3);
+    assert!(stats.sparse_capacity >= 7);
+    assert_eq!(overlay.instance_to_sparse_ratio(), (70, 7));
+    assert!(stats.to_string().contains("70 instances, 7 overrides"));
+}
+
+#[test]
+fn clear_prototype_data_keeps_overrides() {
+    let mut overlay: Overlay<u32> = [1, 2].into_iter().collect();
+    overlay.push_instance();
+    overlay.push_instance();
+    overlay.set(1, 9);
+
+    overlay.clear_prototype_data();
+    assert_eq!(overlay.prototypes_len(), 0);
+    assert_eq!(overlay.instances_len(), 2);
+    assert_eq!(*overlay.get(1, 0), 9);
+    overlay.check_invariants();
+
+    overlay.extend([5]);
+    assert_eq!(*overlay.get(0, 0), 5);
+}
+
+#[test]
+fn update_filtered_sets_and_optionally_clears() {
+    let mut overlay: Overlay<u32> = [1, 2].into_iter().collect();
+    for i in 0..4 {
+        overlay.push_instance();
+        overlay.set(i, 100);
+    }
+    let proto_ids = [0, 1, 0, 1];
+
+    // bump instances 0 and 2 only; the rest keep their overrides
+    let updated = overlay.update_filtered(4, &proto_ids, false, |i, v| (i % 2 == 0).then_some(v + 1));
+    assert_eq!(updated, 2);
+    assert_eq!(overlay.to_dense_vec(&proto_ids), [101, 100, 101, 100]);
+
+    // keep instance 0's override, reset everything else to its prototype
+    let updated = overlay.update_filtered(4, &proto_ids, true, |i, v| (i == 0).then_some(*v));
+    assert_eq!(updated, 1);
+    assert_eq!(overlay.to_dense_vec(&proto_ids), [101, 2, 1, 2]);
+    assert_eq!(overlay.override_count(), 1);
+}
+
+#[test]
+fn swap_with_prototype_exchanges_values() {
+    let mut overlay: Overlay<u32> = [1].into_iter().collect();
+    overlay.push_instance();
+    overlay.push_instance();
+    overlay.set(0, 7);
+
+    overlay.swap_with_prototype(0, 0);
+    assert_eq!(overlay.dense_data, [7]);
+    assert_eq!(*overlay.get(0, 0), 1);
+    assert_eq!(*overlay.get(1, 0), 7);
+
+    // no override and equal values: nothing to keep
+    overlay.swap_with_prototype(1, 0);
+    assert_eq!(overlay.dense_data, [7]);
+    assert!(!overlay.has_override(1));
+}
+
+#[test]
+fn copy_prototype_range_appends_slice() {
+    let mut overlay: Overlay<u32> = [1].into_iter().collect();
+    let other: Overlay<u32> = [10, 20, 30, 40].into_iter().collect();
+
+    overlay.copy_prototype_range(&other, 1..3);
+    assert_eq!(overlay.dense_data, [1, 20, 30]);
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn copy_prototype_range_checks_bounds() {
+    let mut overlay = Overlay::<u32>::default();
+    let other: Overlay<u32> = [10].into_iter().collect();
+    overlay.copy_prototype_range(&other, 0..2);
+}
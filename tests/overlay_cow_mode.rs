@@ -0,0 +1,96 @@
+use grug_soa::{CowMode, Overlay};
+
+#[test]
+fn lazy_mark_defers_clone_until_commit() {
+    let mut overlay = Overlay::with_cow_mode(CowMode::LazyMark);
+    overlay.dense_data = vec![10u32, 20];
+    for _ in 0..3 {
+        overlay.push_instance();
+    }
+    let proto_ids = [0, 1, 1];
+
+    overlay.mark_for_write(1, 1);
+    overlay.mark_for_write(2, 1);
+    assert!(overlay.is_marked_for_write(1));
+    assert_eq!(overlay.override_count(), 0);
+    assert_eq!(*overlay.get(1, 1), 20);
+
+    // Entity 2's mark follows it into slot 0.
+    overlay.swap_remove_instance(0);
+    assert!(overlay.is_marked_for_write(0));
+    overlay.check_invariants();
+
+    assert_eq!(overlay.commit_lazy_overrides(&proto_ids[1..]), 2);
+    assert_eq!(overlay.override_count(), 2);
+    assert!(!overlay.is_marked_for_write(0));
+    assert_eq!(*overlay.get(0, 1), 20);
+    overlay.check_invariants();
+}
+
+#[test]
+fn eager_clone_overrides_immediately() {
+    let mut overlay = Overlay::default();
+    overlay.dense_data = vec![10u32];
+    overlay.push_instance();
+
+    overlay.mark_for_write(0, 0);
+    assert_eq!(overlay.cow_mode(), CowMode::EagerClone);
+    assert!(overlay.has_override(0));
+    assert!(!overlay.is_marked_for_write(0));
+}
+
+#[test]
+fn get_or_insert_with_only_transforms_on_first_write() {
+    let mut overlay = Overlay::default();
+    overlay.dense_data = vec![10u32];
+    overlay.push_instance();
+
+    *overlay.get_or_insert_with(0, 0, |proto| proto * 2) += 1;
+    assert_eq!(*overlay.get_or_insert_with(0, 0, |_| unreachable!()), 21);
+    assert_eq!(overlay.dense_data, [10]);
+}
+
+#[test]
+fn pop_override_returns_the_value() {
+    let mut overlay = Overlay::default();
+    overlay.dense_data = vec![String::from("proto")];
+    overlay.push_instance();
+    overlay.set(0, String::from("mine"));
+
+    assert_eq!(overlay.pop_override(0).as_deref(), Some("mine"));
+    assert_eq!(overlay.pop_override(0), None);
+    assert_eq!(overlay.get(0, 0), "proto");
+    overlay.check_invariants();
+}
+
+#[test]
+fn iter_changed_vs_snapshot_reports_set_cleared_and_modified() {
+    let mut overlay = Overlay::default();
+    overlay.dense_data = vec![0u32];
+    for i in 0..130 {
+        overlay.push_instance();
+        if i % 3 == 0 {
+            overlay.set(i, 1);
+        }
+    }
+    let snapshot = overlay.snapshot();
+
+    overlay.set(0, 1); // same value: unchanged
+    overlay.set(3, 2); // modified
+    overlay.clear_override(6); // cleared
+    overlay.set(128, 5); // newly set, in a later word
+
+    let changed: Vec<usize> = overlay.iter_changed_vs_snapshot(&snapshot).collect();
+    assert_eq!(changed, [3, 6, 128]);
+}
+
+#[test]
+fn conditional_set_only_when_pred_holds() {
+    let mut overlay: Overlay<i32> = [10].into_iter().collect();
+    overlay.push_instance();
+
+    assert!(overlay.conditional_set(0, 0, |hp| *hp > 0, 0));
+    assert_eq!(*overlay.get(0, 0), 0);
+    assert!(!overlay.conditional_set(0, 0, |hp| *hp > 0, -5));
+    assert_eq!(*overlay.get(0, 0), 0);
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "hooks")]
+
+use std::sync::{Arc, Mutex};
+
+use grug_soa::Overlay;
+
+#[test]
+fn hooks_see_sets_and_clears() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (on_set, on_clear) = (Arc::clone(&events), Arc::clone(&events));
+    let mut overlay = Overlay::default()
+        .with_set_hook(move |id, v: &u32| on_set.lock().unwrap().push(format!("set {id} {v}")))
+        .with_clear_hook(move |id| on_clear.lock().unwrap().push(format!("clear {id}")));
+    overlay.dense_data.push(7);
+    overlay.push_instance();
+    overlay.push_instance();
+
+    overlay.set(0, 1);
+    *overlay.get_mut(1, 0) += 1; // COW fires once, with the prototype value
+    *overlay.get_mut(1, 0) += 1;
+    overlay.clear_override(1);
+    overlay.clear_override(1); // nothing left to clear
+
+    assert_eq!(*events.lock().unwrap(), ["set 0 1", "set 1 7", "clear 1"]);
+}
+
+#[test]
+fn deserialize_delta_fires_hooks() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let (on_set, on_clear) = (Arc::clone(&events), Arc::clone(&events));
+    let mut overlay = Overlay::default()
+        .with_set_hook(move |id, v: &u32| on_set.lock().unwrap().push(format!("set {id} {v}")))
+        .with_clear_hook(move |id| on_clear.lock().unwrap().push(format!("clear {id}")));
+    overlay.dense_data.push(7);
+    for _ in 0..3 {
+        overlay.push_instance();
+    }
+    overlay.set(0, 1);
+    overlay.set(1, 2);
+    events.lock().unwrap().clear();
+
+    overlay.deserialize_delta(serde_json::json!({ "1": 5, "2": 6 })).unwrap();
+    assert_eq!(*events.lock().unwrap(), ["clear 0", "set 1 5", "set 2 6"]);
+    overlay.check_invariants();
+}
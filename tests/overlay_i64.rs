@@ -0,0 +1,19 @@
+use grug_soa::OverlayI64;
+
+#[test]
+fn sentinel_overrides_survive_swap_remove() {
+    let mut overlay = OverlayI64::default();
+    overlay.dense_data.push(0u32);
+    overlay.push_instance();
+    overlay.push_instance();
+
+    overlay.set(-1, 99);
+    overlay.set(1, 7);
+    assert!(overlay.is_valid_id(-5));
+    assert!(!overlay.is_valid_id(2));
+
+    overlay.swap_remove_instance(0);
+    assert_eq!(*overlay.get(0, 0), 7);
+    assert_eq!(*overlay.get(-1, 0), 99);
+    assert_eq!(overlay.override_count(), 2);
+}
@@ -0,0 +1,81 @@
+use grug_soa::{Overlay, PrototypeId};
+use serde_json::json;
+
+#[repr(usize)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Proto {
+    Goblin,
+    Orc,
+}
+
+impl From<Proto> for usize {
+    fn from(proto: Proto) -> usize {
+        proto as usize
+    }
+}
+
+impl From<usize> for Proto {
+    fn from(id: usize) -> Proto {
+        match id {
+            0 => Proto::Goblin,
+            1 => Proto::Orc,
+            _ => panic!("no prototype {id}"),
+        }
+    }
+}
+
+#[test]
+fn index_by_enum_prototype_id() {
+    let mut overlay: Overlay<u32> = [10, 20].into_iter().collect();
+    overlay.push_instance();
+    overlay.set(0, 99);
+
+    assert_eq!(overlay[Proto::Goblin], 10);
+    assert_eq!(overlay[Proto::Orc], 20);
+    assert_eq!(overlay[1usize], 20);
+    assert_eq!(overlay[PrototypeId(0)], 10);
+}
+
+#[derive(Default)]
+struct Mobs {
+    prototype_id: Vec<Proto>,
+    hp: Vec<u32>,
+    name: Overlay<String>,
+}
+
+grug_soa::impl_load_prototype!(Mobs { prototype_id: Proto, hp: u32, name: String });
+
+fn prototypes() -> Mobs {
+    let mut prototypes = Mobs::default();
+    prototypes.load_prototype(json!({ "hp": 5, "name": "goblin" }));
+    prototypes.load_prototype(json!({ "hp": 50, "name": "orc" }));
+    prototypes
+}
+
+#[test]
+fn enum_prototype_id_column() {
+    let prototypes = prototypes();
+    assert_eq!(prototypes.prototype_id, [Proto::Goblin, Proto::Orc]);
+
+    let mut live = Mobs::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, Proto::Orc.into());
+    live.spawn_entity(&prototypes, Proto::Goblin.into());
+    live.name.set(1, "Gob".into());
+    assert_eq!(live.prototype_id, [Proto::Orc, Proto::Goblin]);
+    assert_eq!(live.name[live.prototype_id[0]], "orc");
+
+    let names: Vec<String> = live.iter_field(|m| &m.name).map(|n| n.into_owned()).collect();
+    assert_eq!(names, ["orc", "Gob"]);
+    assert_eq!(live.group_by_prototype(&prototypes), [vec![1], vec![0]]);
+    assert_eq!(live.group_by_prototype_lazy(&prototypes).get(1), [0]);
+    assert_eq!(live.validate_prototype_ids(&prototypes), Ok(()));
+    assert_eq!(live.entity_to_json(1), json!({ "hp": 5, "name": "Gob" }));
+
+    live.migrate_prototype(&prototypes, 1, Proto::Orc.into());
+    assert_eq!(live.prototype_id[1], Proto::Orc);
+    assert_eq!(live.reset_all_entities_of_prototype(Proto::Orc.into()), 1);
+    assert_eq!(live.name.get(1, usize::from(live.prototype_id[1])), "orc");
+
+    live.remap_swapped_prototypes(0, 1);
+    assert_eq!(live.prototype_id, [Proto::Goblin, Proto::Goblin]);
+}
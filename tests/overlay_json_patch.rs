@@ -0,0 +1,37 @@
+use grug_soa::{Overlay, json_merge_patch};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Stats {
+    hp: i32,
+    name: String,
+    tags: Option<Vec<String>>,
+}
+
+#[test]
+fn merge_patch_follows_rfc_7396() {
+    let mut target = json!({ "a": "b", "c": { "d": "e", "f": "g" } });
+    json_merge_patch(&mut target, &json!({ "a": "z", "c": { "f": null }, "n": [1] }));
+    assert_eq!(target, json!({ "a": "z", "c": { "d": "e" }, "n": [1] }));
+
+    json_merge_patch(&mut target, &json!(["replaced"]));
+    assert_eq!(target, json!(["replaced"]));
+}
+
+#[test]
+fn apply_json_patch_sets_clears_and_rejects() {
+    let goblin = Stats { hp: 10, name: "goblin".into(), tags: Some(vec!["green".into()]) };
+    let mut overlay: Overlay<Stats> = [goblin.clone()].into_iter().collect();
+    overlay.push_instance();
+
+    overlay.apply_json_patch(0, 0, &json!({ "hp": 3, "tags": null })).unwrap();
+    assert_eq!(*overlay.get(0, 0), Stats { hp: 3, name: "goblin".into(), tags: None });
+
+    assert!(overlay.apply_json_patch(0, 0, &json!({ "hp": "lots" })).is_err());
+    assert_eq!(overlay.get(0, 0).hp, 3);
+
+    overlay.apply_json_patch(0, 0, &serde_json::Value::Null).unwrap();
+    assert!(!overlay.has_override(0));
+    assert_eq!(*overlay.get(0, 0), goblin);
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use grug_soa::{ChainedSource, CowMode, Either, InstanceId, Overlay, OverlayWithMeta, OverlayWithProtoFn, PrototypeId};
+
+/// Prototypes 10, 20, 30 and `n` instances, none overridden.
+fn overlay(n: usize) -> Overlay<u32> {
+    let mut overlay: Overlay<u32> = [10, 20, 30].into_iter().collect();
+    for _ in 0..n {
+        overlay.push_instance();
+    }
+    overlay
+}
+
+#[test]
+fn overrides_in_range_are_not_dangling() {
+    let mut overlay = overlay(2);
+    overlay.set(1, 5);
+    overlay.swap_remove_instance(0);
+    overlay.assert_no_dangling_overrides();
+}
+
+#[test]
+#[should_panic(expected = "Overlay dangling override: 7 >= 2")]
+fn dangling_overrides_are_detected() {
+    let mut overlay = overlay(2);
+    overlay.sparse_data.insert(7, 5);
+    overlay.assert_no_dangling_overrides();
+}
+
+#[test]
+fn iter_sparse_sorted_is_ascending() {
+    let mut overlay = overlay(200);
+    for i in [150, 3, 64, 199, 0] {
+        overlay.set(i, i as u32);
+    }
+    let ids: Vec<usize> = overlay.iter_sparse_sorted().map(|(i, _)| i).collect();
+    assert_eq!(ids, [0, 3, 64, 150, 199]);
+    assert!(overlay.iter_sparse_sorted().all(|(i, &v)| v == i as u32));
+
+    let mut unordered: Vec<(usize, u32)> = overlay.iter_overrides().map(|(i, &v)| (i, v)).collect();
+    unordered.sort();
+    assert_eq!(unordered, overlay.iter_sparse_sorted().map(|(i, &v)| (i, v)).collect::<Vec<_>>());
+}
+
+#[test]
+fn proto_fn_picks_the_prototype() {
+    let mut with_fn = OverlayWithProtoFn::new(overlay(4), |i| i % 3);
+    assert_eq!(*with_fn.get(2), 30);
+    assert_eq!(*with_fn.get(3), 10);
+
+    *with_fn.get_mut(1) += 1;
+    assert_eq!(*with_fn.get(1), 21);
+    assert!(with_fn.overlay.has_override(1));
+}
+
+#[test]
+fn meta_follows_its_instance() {
+    let mut with_meta = OverlayWithMeta::<u32, &str>::default();
+    with_meta.overlay.dense_data.push(10);
+    for _ in 0..3 {
+        with_meta.push_instance();
+    }
+    with_meta.set_with_meta(2, 99, "tick 7");
+
+    assert_eq!(with_meta.get_meta(2), Some(&"tick 7"));
+    assert_eq!(with_meta.get_meta(0), None);
+    assert_eq!(with_meta.get_meta(5), None);
+
+    with_meta.swap_remove_instance(0);
+    assert_eq!(with_meta.get_meta(0), Some(&"tick 7"));
+    assert_eq!(*with_meta.overlay.get(0, 0), 99);
+    assert_eq!(with_meta.meta.len(), 2);
+}
+
+#[test]
+fn delta_round_trip_skips_overrides_equal_to_the_prototype() {
+    let proto_ids = [0, 1, 2];
+    let mut overlay = overlay(3);
+    overlay.set(0, 11);
+    overlay.set(1, 20); // same as prototype 1
+
+    let delta = overlay.serialize_delta(&proto_ids, serde_json::value::Serializer).unwrap();
+    assert_eq!(delta, serde_json::json!({ "0": 11 }));
+
+    let mut remote = self::overlay(3);
+    remote.set(2, 1);
+    remote.deserialize_delta(delta).unwrap();
+    assert_eq!(remote.iter_all(&proto_ids).copied().collect::<Vec<_>>(), [11, 20, 30]);
+    assert!(!remote.has_override(2));
+
+    let mut short = self::overlay(0);
+    assert!(short.deserialize_delta(serde_json::json!({ "0": 1 })).is_err());
+}
+
+#[test]
+fn deserialize_delta_drops_lazy_marks() {
+    let mut overlay = Overlay::with_cow_mode(CowMode::LazyMark);
+    overlay.dense_data.push(10u32);
+    for _ in 0..2 {
+        overlay.push_instance();
+    }
+    overlay.mark_for_write(1, 0);
+
+    overlay.deserialize_delta(serde_json::json!({ "0": 5 })).unwrap();
+    assert!(!overlay.is_marked_for_write(1));
+    assert_eq!(overlay.commit_lazy_overrides(&[0, 0]), 0);
+    assert_eq!(overlay.override_count(), 1);
+}
+
+#[test]
+fn set_many_and_set_range() {
+    let mut overlay = overlay(130);
+    overlay.set_many(&[1, 64, 129], 7);
+    overlay.set_range(60, 66, 8);
+    overlay.set_many(&[], 9);
+    overlay.set_range(5, 5, 9);
+    overlay.check_invariants();
+
+    let ids: Vec<usize> = overlay.iter_sparse_sorted().map(|(i, _)| i).collect();
+    assert_eq!(ids, [1, 60, 61, 62, 63, 64, 65, 129]);
+    assert_eq!(*overlay.get(1, 0), 7);
+    assert_eq!(*overlay.get(64, 0), 8);
+    assert_eq!(*overlay.get(129, 0), 7);
+}
+
+#[test]
+fn push_prototypes_from_slice_appends() {
+    let mut overlay = overlay(1);
+    overlay.push_prototypes_from_slice(&[40, 50]);
+    assert_eq!(overlay.dense_data, [10, 20, 30, 40, 50]);
+    assert_eq!(*overlay.get(0, 4), 50);
+}
+
+#[test]
+fn prototype_sources_and_global_default() {
+    let mut overlay = overlay(2);
+    overlay.set(1, 1);
+
+    let base = vec![100, 200];
+    let patch: HashMap<usize, u32> = [(1, 250)].into();
+    let chain = ChainedSource { primary: &patch, fallback: &base };
+    assert_eq!(*overlay.get_with_source(0, 0, &chain), 100);
+    assert_eq!(*overlay.get_with_source(0, 1, &chain), 250);
+    assert_eq!(*overlay.get_with_source(1, 0, &chain), 1);
+
+    assert_eq!(*overlay.get_with_fallback(0, usize::MAX, &0), 0);
+    assert_eq!(*overlay.get_with_fallback(0, 2, &0), 30);
+    assert_eq!(overlay.global_default(), None);
+    overlay.set_global_default(5);
+    assert_eq!(overlay.global_default(), Some(&5));
+    assert_eq!(*overlay.get_or_global_default(0, 9), 5);
+    assert_eq!(*overlay.get_or_global_default(1, 9), 1);
+}
+
+#[test]
+fn clear_overrides_for_prototype_only_touches_that_prototype() {
+    let proto_ids = [0, 1, 0, 2];
+    let mut overlay = overlay(4);
+    for i in 0..4 {
+        overlay.set(i, 1);
+    }
+    assert_eq!(overlay.clear_overrides_for_prototype(0, &proto_ids), 2);
+    assert_eq!(overlay.iter_all(&proto_ids).copied().collect::<Vec<_>>(), [10, 1, 10, 1]);
+    assert_eq!(overlay.clear_overrides_for_prototype(0, &proto_ids), 0);
+}
+
+#[test]
+fn lazy_mut_clones_only_on_write() {
+    let mut overlay = overlay(2);
+    {
+        let handle = overlay.get_lazy_mut(0, 1);
+        assert_eq!(*handle, 20);
+    }
+    assert!(!overlay.has_override(0));
+
+    *overlay.get_lazy_mut(1, 1) += 2;
+    assert!(overlay.has_override(1));
+    assert_eq!(*overlay.get(1, 1), 22);
+}
+
+#[test]
+fn scan_and_evict_converts_dense_overlays() {
+    let proto_ids = [0, 1, 2, 0];
+    let mut sparse = overlay(4);
+    sparse.set(3, 4);
+    let Either::Left(sparse) = sparse.scan_and_evict(0.5, &proto_ids) else {
+        panic!("a quarter overridden is below the threshold");
+    };
+    assert_eq!(sparse.sparsity(), 0.25);
+
+    let mut dense = overlay(4);
+    dense.set_range(0, 3, 7);
+    assert_eq!(dense.clone().upgrade_to_fully_dense(&proto_ids), [7, 7, 7, 10]);
+    let Either::Right(values) = dense.scan_and_evict(0.5, &proto_ids) else {
+        panic!("three quarters overridden is above the threshold");
+    };
+    assert_eq!(values, [7, 7, 7, 10]);
+}
+
+#[test]
+fn transform_prototypes_leaves_overrides() {
+    let mut overlay = overlay(2);
+    overlay.set(1, 1);
+    overlay.transform_prototypes(|v| v * 2);
+    assert_eq!(overlay.dense_data, [20, 40, 60]);
+    assert_eq!(*overlay.get(0, 2), 60);
+    assert_eq!(*overlay.get(1, 2), 1);
+}
+
+#[test]
+fn copy_and_clone_overrides() {
+    let mut overlay = overlay(3);
+    overlay.set(0, 5);
+    overlay.set(2, 6);
+
+    overlay.copy_override(0, 1);
+    assert_eq!(*overlay.get(1, 0), 5);
+    overlay.copy_override(1, 2);
+    assert_eq!(*overlay.get(2, 0), 5);
+
+    overlay.clear_override(0);
+    overlay.copy_override(0, 2);
+    assert!(!overlay.has_override(2));
+
+    let clone = overlay.clone_instance(1);
+    assert_eq!(clone, 3);
+    assert_eq!(*overlay.get(clone, 0), 5);
+    overlay.check_invariants();
+}
+
+#[test]
+fn get_both_mut_copies_on_write() {
+    let mut overlay = overlay(3);
+    overlay.set(2, 5);
+
+    let (a, b) = overlay.get_both_mut(0, 1, 2, 0);
+    assert_eq!((*a, *b), (20, 5));
+    std::mem::swap(a, b);
+    assert_eq!(*overlay.get(0, 1), 5);
+    assert_eq!(*overlay.get(2, 0), 20);
+    assert!(!overlay.has_override(1));
+    overlay.check_invariants();
+}
+
+#[test]
+#[should_panic(expected = "get_both_mut: both ids are 1")]
+fn get_both_mut_rejects_the_same_instance() {
+    overlay(2).get_both_mut(1, 0, 1, 0);
+}
+
+#[test]
+fn defrag_keeps_every_override() {
+    let mut overlay = overlay(1000);
+    for i in 0..1000 {
+        overlay.set(i, i as u32);
+    }
+    for i in 10..1000 {
+        overlay.clear_override(i);
+    }
+    let before = overlay.sparse_data.capacity();
+    overlay.defrag_sparse_data();
+
+    assert!(overlay.sparse_data.capacity() < before);
+    assert_eq!(overlay.override_count(), 10);
+    assert!((0..10).all(|i| *overlay.get(i, 0) == i as u32));
+    overlay.check_invariants();
+}
+
+#[test]
+fn typed_id_accessors() {
+    let mut overlay = overlay(2);
+    overlay.set(InstanceId(1), 3);
+    assert!(overlay.has_override(InstanceId(1)));
+    assert_eq!(*overlay.get(InstanceId(1), PrototypeId(0)), 3);
+    *overlay.get_mut(InstanceId(0), PrototypeId(2)) += 1;
+    assert_eq!(*overlay.get(InstanceId(0), PrototypeId(2)), 31);
+    // plain usize ids still work
+    assert_eq!(*overlay.get(0, 2), 31);
+
+    overlay.clear_override(InstanceId(1));
+    assert_eq!(*overlay.get(InstanceId(1), PrototypeId(0)), 10);
+    assert_eq!(overlay.pop_override(InstanceId(0)), Some(31));
+}
+
+#[derive(Default)]
+struct Typed {
+    prototype_id: Vec<PrototypeId>,
+    hp: Overlay<u32>,
+}
+
+grug_soa::impl_load_prototype!(Typed { prototype_id: PrototypeId, hp: u32 });
+
+#[test]
+fn typed_prototype_id_column() {
+    let mut prototypes = Typed::default();
+    prototypes.load_prototype(serde_json::json!({ "hp": 5 }));
+    prototypes.load_prototype(serde_json::json!({ "hp": 7 }));
+    assert_eq!(prototypes.prototype_id, [PrototypeId(0), PrototypeId(1)]);
+
+    let mut live = Typed::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 1);
+    let entity = InstanceId(0);
+    assert_eq!(*live.hp.get(entity, live.prototype_id[0]), 7);
+    live.hp.set(entity, 9);
+    assert_eq!(*live.hp.get(entity, live.prototype_id[0]), 9);
+}
+
+#[cfg(feature = "ordered_overrides")]
+#[test]
+fn insertion_order_is_kept() {
+    let mut overlay = overlay(5);
+    for i in [3, 0, 4] {
+        overlay.set(i, i as u32);
+    }
+    let order: Vec<usize> = overlay.iter_overrides_insertion_order().map(|(i, _)| i).collect();
+    assert_eq!(order, [3, 0, 4]);
+    assert_eq!(overlay.first_override(), Some((3, &3)));
+    assert_eq!(overlay.last_override(), Some((4, &4)));
+
+    overlay.clear_override(3);
+    assert_eq!(overlay.first_override(), Some((0, &0)));
+}
+
+#[cfg(feature = "ordered_overrides")]
+#[test]
+fn deserialize_delta_keeps_insertion_order() {
+    let mut overlay = overlay(4);
+    for i in [2, 0, 1] {
+        overlay.set(i, 1);
+    }
+    overlay.deserialize_delta(serde_json::json!({ "0": 5, "3": 6 })).unwrap();
+    let order: Vec<usize> = overlay.iter_overrides_insertion_order().map(|(i, _)| i).collect();
+    assert_eq!(order, [0, 3]);
+    overlay.check_invariants();
+}
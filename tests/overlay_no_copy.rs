@@ -0,0 +1,84 @@
+use grug_soa::OverlayNoCopy;
+use serde::Deserialize;
+use serde_json::json;
+
+/// Stands in for an OS handle: deserializable and defaultable, but not `Clone`.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+struct Handle {
+    path: String,
+}
+
+#[derive(Default)]
+struct Files {
+    prototype_id: Vec<usize>,
+    size: Vec<u32>,
+    handle: OverlayNoCopy<Handle>,
+}
+
+grug_soa::impl_load_prototype!(Files { prototype_id: usize, size: u32, handle: Handle });
+
+fn prototypes() -> Files {
+    let mut prototypes = Files::default();
+    prototypes.load_prototype(json!({ "size": 1, "handle": { "path": "/dev/null" } }));
+    prototypes.load_prototype(json!({ "size": 2, "handle": { "path": "/dev/zero" } }));
+    prototypes
+}
+
+#[test]
+fn spawned_entities_read_the_prototype_value() {
+    let prototypes = prototypes();
+    let mut live = Files::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 1);
+    live.spawn_entity(&prototypes, 0);
+
+    assert_eq!(live.handle.get(0, live.prototype_id[0]).path, "/dev/zero");
+    assert_eq!(live.handle.get(1, live.prototype_id[1]).path, "/dev/null");
+    // shared, not copied
+    assert!(std::sync::Arc::ptr_eq(&live.handle.dense_data[0], &prototypes.handle.dense_data[0]));
+}
+
+#[test]
+fn set_and_clear_own_values() {
+    let prototypes = prototypes();
+    let mut live = Files::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 0);
+
+    live.handle.set(0, Box::new(Handle { path: "/tmp/x".into() }));
+    live.handle.get_mut(0).path.push('y');
+    assert_eq!(live.handle.get(0, 0).path, "/tmp/xy");
+    assert_eq!(live.handle.clear_override(0).unwrap().path, "/tmp/xy");
+    assert_eq!(live.handle.get(0, 0).path, "/dev/null");
+}
+
+#[test]
+fn migrate_keep_values_pins_the_old_prototype_value() {
+    let prototypes = prototypes();
+    let mut live = Files::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 0);
+    live.spawn_entity(&prototypes, 0);
+
+    live.migrate_prototype_keep_values(&prototypes, 0, 1);
+    assert_eq!(live.handle.get(0, live.prototype_id[0]).path, "/dev/null");
+    assert!(live.handle.is_pinned(0));
+
+    // the pin moves with swap_remove
+    live.swap_remove(1);
+    assert_eq!(live.handle.get(0, live.prototype_id[0]).path, "/dev/null");
+
+    live.handle.clear_override(0);
+    assert_eq!(live.handle.get(0, live.prototype_id[0]).path, "/dev/zero");
+}
+
+#[test]
+fn swap_remove_moves_the_last_instance() {
+    let prototypes = prototypes();
+    let mut live = Files::new_from_prototypes(&prototypes);
+    for _ in 0..70 {
+        live.spawn_entity(&prototypes, 0);
+    }
+    live.handle.set(69, Box::new(Handle { path: "last".into() }));
+    live.swap_remove(3);
+    assert_eq!(live.len(), 69);
+    assert_eq!(live.handle.get(3, 0).path, "last");
+    assert!(!live.handle.has_override(68));
+}
@@ -0,0 +1,49 @@
+use grug_soa::Overlay;
+
+const PROTOTYPES: usize = 200;
+const INSTANCES: usize = 500;
+
+fn proto_id(instance_id: usize) -> usize {
+    instance_id * 7 % PROTOTYPES
+}
+
+/// `PROTOTYPES` prototypes and `INSTANCES` instances, every other instance overridden.
+fn overlay() -> Overlay<String> {
+    let mut overlay: Overlay<String> = (0..PROTOTYPES).map(|p| format!("prototype {p}")).collect();
+    for i in 0..INSTANCES {
+        overlay.push_instance();
+        if i % 2 == 0 {
+            overlay.set(i, format!("override {i}"));
+        }
+    }
+    overlay
+}
+
+#[test]
+fn json_round_trip_rebuilds_presence() {
+    let original = overlay();
+    let json = serde_json::to_string(&original).unwrap();
+    let deserialized: Overlay<String> = serde_json::from_str(&json).unwrap();
+
+    original.check_invariants();
+    deserialized.check_invariants();
+    assert_eq!(deserialized.instances_len(), INSTANCES);
+    assert_eq!(deserialized.prototypes_len(), PROTOTYPES);
+    assert_eq!(deserialized.presence, original.presence);
+    for i in 0..INSTANCES {
+        assert_eq!(deserialized.get(i, proto_id(i)), original.get(i, proto_id(i)), "instance {i}");
+        assert_eq!(deserialized.has_override(i), original.has_override(i));
+    }
+}
+
+#[test]
+fn rejects_override_past_instance_len() {
+    let json = r#"{ "dense_data": [1], "instance_len": 2, "overrides": [[2, 5]] }"#;
+    assert!(serde_json::from_str::<Overlay<u32>>(json).is_err());
+}
+
+#[test]
+fn rejects_duplicate_override() {
+    let json = r#"{ "dense_data": [1], "instance_len": 2, "overrides": [[1, 5], [1, 6]] }"#;
+    assert!(serde_json::from_str::<Overlay<u32>>(json).is_err());
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use grug_soa::OverlayShared;
+use serde_json::json;
+
+#[derive(Default)]
+struct Sprites {
+    prototype_id: Vec<usize>,
+    frame: Vec<u32>,
+    path: OverlayShared<String>,
+}
+
+grug_soa::impl_load_prototype!(Sprites { prototype_id: usize, frame: u32, path: String });
+
+fn prototypes() -> Sprites {
+    let mut prototypes = Sprites::default();
+    prototypes.load_prototype(json!({ "frame": 0, "path": "orc.png" }));
+    prototypes.load_prototype(json!({ "frame": 1, "path": "elf.png" }));
+    prototypes
+}
+
+#[test]
+fn runtime_tables_share_the_prototype_arc() {
+    let prototypes = prototypes();
+    let a = Sprites::new_from_prototypes(&prototypes);
+    let b = Sprites::new_from_prototypes(&prototypes);
+
+    assert!(Arc::ptr_eq(&a.path.dense_data, &prototypes.path.dense_data));
+    assert!(Arc::ptr_eq(&b.path.dense_data, &prototypes.path.dense_data));
+    assert_eq!(Arc::strong_count(&prototypes.path.dense_data), 3);
+
+    drop(a);
+    assert_eq!(Arc::strong_count(&prototypes.path.dense_data), 2);
+}
+
+#[test]
+fn copy_on_write_stays_in_its_own_table() {
+    let prototypes = prototypes();
+    let mut a = Sprites::new_from_prototypes(&prototypes);
+    let mut b = Sprites::new_from_prototypes(&prototypes);
+    a.spawn_entity(&prototypes, 0);
+    b.spawn_entity(&prototypes, 0);
+
+    a.path.get_mut(0, 0).push_str(".bak");
+    assert_eq!(a.path.get(0, 0), "orc.png.bak");
+    assert_eq!(b.path.get(0, 0), "orc.png");
+    assert!(a.path.has_override(0));
+    assert!(!b.path.has_override(0));
+
+    // Overrides never touch the shared prototype data.
+    assert_eq!(prototypes.path.dense_data[0], "orc.png");
+    assert!(Arc::ptr_eq(&a.path.dense_data, &b.path.dense_data));
+}
+
+#[test]
+fn swap_remove_keeps_overrides_per_table() {
+    let prototypes = prototypes();
+    let mut a = Sprites::new_from_prototypes(&prototypes);
+    let mut b = Sprites::new_from_prototypes(&prototypes);
+    for proto in [0, 1, 0] {
+        a.spawn_entity(&prototypes, proto);
+        b.spawn_entity(&prototypes, proto);
+    }
+    a.path.set(2, "a2.png".into());
+    b.path.set(1, "b1.png".into());
+
+    a.swap_remove(0);
+    assert_eq!(a.prototype_id, [0, 1]);
+    assert_eq!(a.path.get(0, a.prototype_id[0]), "a2.png");
+    assert_eq!(a.path.get(1, a.prototype_id[1]), "elf.png");
+
+    b.swap_remove(0);
+    assert_eq!(b.path.get(0, b.prototype_id[0]), "orc.png");
+    assert_eq!(b.path.get(1, b.prototype_id[1]), "b1.png");
+    assert!(!b.path.has_override(0));
+}
+
+#[test]
+fn loading_into_shared_data_clones_it_first() {
+    let mut prototypes = prototypes();
+    let live = Sprites::new_from_prototypes(&prototypes);
+
+    prototypes.load_prototype(json!({ "frame": 2, "path": "imp.png" }));
+    assert_eq!(prototypes.path.prototypes_len(), 3);
+    assert_eq!(live.path.prototypes_len(), 2);
+    assert!(!Arc::ptr_eq(&live.path.dense_data, &prototypes.path.dense_data));
+}
@@ -0,0 +1,145 @@
+use grug_soa::Overlay;
+
+const PROTO: u32 = 1000;
+
+/// Overlay with `n` instances of prototype 0, where `overridden(i)` instances get value `i`.
+fn overlay_with(n: usize, overridden: impl Fn(usize) -> bool) -> Overlay<u32> {
+    let mut overlay = Overlay::default();
+    overlay.dense_data.push(PROTO);
+    for i in 0..n {
+        overlay.push_instance();
+        if overridden(i) {
+            overlay.set(i, i as u32);
+        }
+    }
+    overlay
+}
+
+fn expected(i: usize, overridden: &impl Fn(usize) -> bool) -> u32 {
+    if overridden(i) { i as u32 } else { PROTO }
+}
+
+/// Swap-removes `index` from an overlay of `n` instances and checks every remaining value.
+fn check_swap_remove(n: usize, index: usize, overridden: impl Fn(usize) -> bool) {
+    let mut overlay = overlay_with(n, &overridden);
+    overlay.swap_remove_instance(index);
+    overlay.check_invariants();
+
+    let last = n - 1;
+    assert_eq!(overlay.instances_len(), last);
+    for i in 0..last {
+        let source = if i == index { last } else { i };
+        assert_eq!(
+            *overlay.get(i, 0),
+            expected(source, &overridden),
+            "n={n} index={index} instance={i}"
+        );
+        assert_eq!(overlay.has_override(i), overridden(source));
+    }
+}
+
+#[test]
+fn remove_only_instance() {
+    check_swap_remove(1, 0, |_| true);
+    check_swap_remove(1, 0, |_| false);
+}
+
+#[test]
+fn remove_every_position_in_full_word() {
+    for index in 0..64 {
+        check_swap_remove(64, index, |i| i % 3 == 0);
+        check_swap_remove(64, index, |i| i % 3 != 0);
+    }
+}
+
+#[test]
+fn remove_across_word_boundary() {
+    // index and last in different presence words.
+    check_swap_remove(65, 63, |_| true);
+    check_swap_remove(65, 63, |i| i == 64);
+    check_swap_remove(65, 63, |i| i == 63);
+    check_swap_remove(65, 0, |i| i == 64);
+}
+
+#[test]
+fn remove_with_and_without_last_override() {
+    for n in [2, 63, 64, 65, 130] {
+        for index in [0, n / 2, n - 2] {
+            check_swap_remove(n, index, |i| i == n - 1);
+            check_swap_remove(n, index, |i| i != n - 1);
+        }
+    }
+}
+
+#[test]
+fn randomized_against_model() {
+    let mut overlay = Overlay::<u32>::default();
+    overlay.dense_data.push(PROTO);
+    let mut model: Vec<Option<u32>> = Vec::new();
+
+    // xorshift, so failures reproduce.
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut rand = |n: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % n as u64) as usize
+    };
+
+    for step in 0..20_000 {
+        let len = model.len();
+        match rand(4) {
+            _ if len == 0 => {
+                overlay.push_instance();
+                model.push(None);
+            }
+            0 => {
+                overlay.push_instance();
+                model.push(None);
+            }
+            1 => {
+                let i = rand(len);
+                let v = rand(1000) as u32;
+                overlay.set(i, v);
+                model[i] = Some(v);
+            }
+            2 => {
+                let i = rand(len);
+                overlay.clear_override(i);
+                model[i] = None;
+            }
+            _ => {
+                let i = rand(len);
+                overlay.swap_remove_instance(i);
+                model.swap_remove(i);
+            }
+        }
+
+        overlay.check_invariants();
+        for (i, v) in model.iter().enumerate() {
+            assert_eq!(*overlay.get(i, 0), v.unwrap_or(PROTO), "step {step} instance {i}");
+        }
+    }
+}
+
+#[test]
+fn drain_instances_shifts_later_instances_down() {
+    for (n, start, end) in [(10, 2, 5), (130, 60, 70), (130, 0, 130), (65, 64, 65), (5, 3, 3)] {
+        let overridden = |i: usize| i.is_multiple_of(3);
+        let mut overlay = overlay_with(n, overridden);
+
+        let drained: Vec<_> = overlay.drain_instances(start..end).collect();
+        let expected_drained: Vec<_> = (start..end)
+            .map(|i| (i, overridden(i).then_some(i as u32)))
+            .collect();
+        assert_eq!(drained, expected_drained, "n={n} range={start}..{end}");
+
+        overlay.check_invariants();
+        let removed = end - start;
+        assert_eq!(overlay.instances_len(), n - removed);
+        for i in 0..n - removed {
+            let source = if i < start { i } else { i + removed };
+            assert_eq!(*overlay.get(i, 0), expected(source, &overridden), "n={n} instance={i}");
+        }
+    }
+}
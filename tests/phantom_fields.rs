@@ -0,0 +1,31 @@
+use std::marker::PhantomData;
+
+use serde_json::json;
+
+struct Enemy;
+
+#[derive(Default)]
+struct Enemies {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    kind: PhantomData<Enemy>,
+}
+
+grug_soa::impl_load_prototype!(Enemies { prototype_id: usize, hp: u32, kind: PhantomData<Enemy> });
+
+#[test]
+fn phantom_fields_are_registered_but_hold_nothing() {
+    let mut prototypes = Enemies::default();
+    prototypes.load_prototype(json!({ "hp": 10 }));
+    prototypes.load_prototype(json!({ "hp": 20, "kind": null }));
+
+    let mut live = Enemies::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 1);
+    live.spawn_entity(&prototypes, 0);
+    live.swap_remove(0);
+
+    assert_eq!(live.hp, [10]);
+    assert!(live.len_consistent());
+    assert_eq!(live.entity_to_json(0), json!({ "hp": 10, "kind": null }));
+    assert_eq!(Enemies::field_size_of("kind"), 0);
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "concurrent")]
+
+use grug_soa::RwOverlay;
+use serde_json::json;
+
+fn overlay_with(n: usize) -> RwOverlay<String> {
+    let mut overlay = RwOverlay::default();
+    overlay.dense_data.push("proto".to_string());
+    for _ in 0..n {
+        overlay.push_instance();
+    }
+    overlay
+}
+
+#[test]
+fn write_guard_copy_on_write_and_clear() {
+    let overlay = overlay_with(2);
+    {
+        let mut guard = overlay.write();
+        guard.get_mut(1, 0).push_str(" edited");
+        assert!(guard.has_override(1));
+        assert!(!guard.has_override(0));
+    }
+    {
+        let guard = overlay.read();
+        assert_eq!(guard.get(0, 0), "proto");
+        assert_eq!(guard.get(1, 0), "proto edited");
+    }
+    assert_eq!(overlay.dense_data[0], "proto");
+
+    overlay.write().clear_override(1);
+    assert_eq!(overlay.read().get(1, 0), "proto");
+}
+
+#[test]
+fn swap_remove_keeps_moved_override() {
+    let mut overlay = overlay_with(65);
+    overlay.write().set(64, "last".to_string());
+    overlay.swap_remove_instance(0);
+    assert_eq!(overlay.instances_len(), 64);
+    assert_eq!(overlay.read().get(0, 0), "last");
+}
+
+#[test]
+fn concurrent_readers_and_writer() {
+    let overlay = overlay_with(64);
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for i in 0..64 {
+                overlay.write().set(i, i.to_string());
+            }
+        });
+        for _ in 0..3 {
+            scope.spawn(|| {
+                for i in 0..64 {
+                    let guard = overlay.read();
+                    let v = guard.get(i, 0);
+                    assert!(v == "proto" || *v == i.to_string());
+                }
+            });
+        }
+    });
+    assert_eq!(overlay.read().get(42, 0), "42");
+}
+
+#[derive(Default)]
+struct Labels {
+    prototype_id: Vec<usize>,
+    text: RwOverlay<String>,
+    size: Vec<u32>,
+}
+
+grug_soa::impl_load_prototype!(Labels { prototype_id: usize, text: String, size: u32 });
+
+#[test]
+fn through_impl_load_prototype() {
+    let mut prototypes = Labels::default();
+    prototypes.load_prototype(json!({ "text": "hello", "size": 12 }));
+    prototypes.load_prototype(json!({ "text": "bye", "size": 8 }));
+
+    let mut live = Labels::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 0);
+    live.spawn_entity(&prototypes, 1);
+    live.spawn_entity(&prototypes, 0);
+    live.text.write().set(2, "custom".to_string());
+
+    assert!(!live.entity_equals(0, 2));
+    assert_eq!(live.diff_entity_from_prototype(2, &prototypes, 0), ["text"]);
+    assert_eq!(live.instance_count_matching("text", &json!("custom")), 1);
+    let texts: Vec<String> = live.iter_field(|soa| &soa.text).map(|t| t.into_owned()).collect();
+    assert_eq!(texts, ["hello", "bye", "custom"]);
+    assert_eq!(live.entity_to_json(2)["text"], "custom");
+
+    live.swap_remove(0);
+    assert_eq!(live.text.read().get(0, live.prototype_id[0]), "custom");
+    assert!(live.len_consistent());
+}
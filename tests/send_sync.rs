@@ -0,0 +1,19 @@
+use grug_soa::{AtomicOverlay, Overlay, OverlayNoCopy, SmallOverlay};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn overlays_are_send_sync() {
+    assert_send_sync::<Overlay<u32>>();
+    assert_send_sync::<Overlay<String>>();
+    assert_send_sync::<OverlayNoCopy<u32>>();
+    assert_send_sync::<AtomicOverlay<u32>>();
+    assert_send_sync::<SmallOverlay<u32>>();
+}
+
+#[cfg(feature = "concurrent")]
+#[test]
+fn rw_overlay_is_send_sync() {
+    assert_send_sync::<grug_soa::RwOverlay<u32>>();
+    assert_send_sync::<grug_soa::RwOverlay<String>>();
+}
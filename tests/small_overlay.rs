@@ -0,0 +1,63 @@
+use grug_soa::{SMALL_OVERLAY_INLINE, SmallOverlay, Storage};
+
+fn small_overlay(instances: usize) -> SmallOverlay<u32> {
+    let mut o = SmallOverlay::default();
+    o.dense_data = vec![100];
+    for _ in 0..instances {
+        o.push_instance();
+    }
+    o
+}
+
+#[test]
+fn small_overlay_spills_past_inline_capacity() {
+    let n = SMALL_OVERLAY_INLINE + 2;
+    let mut o = small_overlay(n);
+
+    for i in 0..SMALL_OVERLAY_INLINE {
+        o.set(i, i as u32);
+    }
+    assert!(!o.is_spilled());
+
+    o.set(SMALL_OVERLAY_INLINE, 42);
+    assert!(o.is_spilled());
+    assert_eq!(o.override_count(), SMALL_OVERLAY_INLINE + 1);
+    for i in 0..SMALL_OVERLAY_INLINE {
+        assert_eq!(*o.get(i, 0), i as u32);
+    }
+    assert_eq!(*o.get(SMALL_OVERLAY_INLINE, 0), 42);
+    assert_eq!(*o.get(n - 1, 0), 100);
+}
+
+#[test]
+fn small_overlay_swap_remove_moves_last_override() {
+    let mut o = small_overlay(3);
+    o.set(2, 7);
+    *o.get_mut(1, 0) += 1;
+
+    o.swap_remove_instance(0);
+    assert_eq!(o.instances_len(), 2);
+    assert_eq!(*o.get(0, 0), 7);
+    assert_eq!(*o.get(1, 0), 101);
+    assert!(!o.has_override(2));
+    assert_eq!(o.override_count(), 2);
+}
+
+#[test]
+fn clone_entity_copies_the_override_slot() {
+    // No prototypes at all: the copy must come from the override, not a prototype lookup.
+    let mut o = SmallOverlay::<u32>::default();
+    for _ in 0..SMALL_OVERLAY_INLINE + 1 {
+        o.push_instance();
+    }
+    o.set(1, 7);
+
+    o.clone_entity(1);
+    let copy = o.instances_len() - 1;
+    assert!(o.has_override(copy));
+    assert_eq!(*o.get(copy, 0), 7);
+
+    o.clone_entity(0);
+    assert!(!o.has_override(o.instances_len() - 1));
+    assert_eq!(o.override_count(), 2);
+}
@@ -0,0 +1,285 @@
+use grug_soa::{Overlay, UnknownFieldError};
+use serde_json::json;
+
+#[derive(Default)]
+struct Units {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    name: Overlay<String>,
+}
+
+grug_soa::impl_load_prototype!(Units { prototype_id: usize, hp: u32, name: String });
+
+fn prototypes() -> Units {
+    let mut prototypes = Units::default();
+    prototypes.load_prototype(json!({ "hp": 10, "name": "grunt" }));
+    prototypes.load_prototype(json!({ "hp": 20, "name": "archer" }));
+    prototypes.load_prototype(json!({ "hp": 30, "name": "mage" }));
+    prototypes
+}
+
+/// Entities of prototypes 0, 1, 2, 0; entity 3 is renamed "Bob".
+fn live(prototypes: &Units) -> Units {
+    let mut live = Units::new_from_prototypes(prototypes);
+    for proto in [0, 1, 2, 0] {
+        live.spawn_entity(prototypes, proto);
+    }
+    live.name.set(3, "Bob".into());
+    live
+}
+
+fn names(units: &Units) -> Vec<String> {
+    units.iter_field(|u| &u.name).map(|name| name.into_owned()).collect()
+}
+
+#[test]
+fn clone_entity_copies_overrides() {
+    let prototypes = prototypes();
+    let mut live = live(&prototypes);
+
+    let copy = live.clone_entity(3);
+    assert_eq!(copy, 4);
+    assert_eq!(live.prototype_id[copy], 0);
+    assert_eq!(live.hp[copy], 10);
+    assert_eq!(live.name.get(copy, 0), "Bob");
+    assert!(live.name.has_override(copy));
+
+    // The copy is independent of its source.
+    live.name.set(copy, "Rob".into());
+    assert_eq!(live.name.get(3, 0), "Bob");
+
+    let plain = live.clone_entity(1);
+    assert!(!live.name.has_override(plain));
+    assert_eq!(live.name.get(plain, 1), "archer");
+}
+
+#[test]
+fn clone_partial_copies_selected_entities_in_order() {
+    let prototypes = prototypes();
+    let live = live(&prototypes);
+
+    let chunk = live.clone_partial(&[3, 1]);
+    assert_eq!(chunk.prototype_id, [0, 1]);
+    assert_eq!(chunk.hp, [10, 20]);
+    assert_eq!(names(&chunk), ["Bob", "archer"]);
+    // Effective values become overrides, independent of the prototype data.
+    assert!(chunk.name.has_override(1));
+    assert!(chunk.len_consistent());
+}
+
+#[test]
+fn partial_spawn_copies_only_named_fields() {
+    let prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+
+    live.partial_spawn_entity(&prototypes, 2, &["name"]);
+    live.partial_spawn_entity(&prototypes, 2, &["hp", "unknown"]);
+
+    assert_eq!(live.prototype_id, [2, 2]);
+    assert_eq!(live.hp, [0, 30]);
+    assert_eq!(names(&live), ["mage", ""]);
+    assert!(!live.name.has_override(0));
+    assert!(live.name.has_override(1));
+}
+
+#[test]
+fn retain_entities_removes_the_rest() {
+    let prototypes = prototypes();
+    let mut live = live(&prototypes);
+
+    assert_eq!(live.retain_entities(|i, u| u.prototype_id[i] == 0), 2);
+    assert_eq!(live.len(), 2);
+    assert_eq!(live.prototype_id, [0, 0]);
+    let mut kept = names(&live);
+    kept.sort();
+    assert_eq!(kept, ["Bob", "grunt"]);
+}
+
+#[test]
+fn find_entity_variants() {
+    let prototypes = prototypes();
+    let live = live(&prototypes);
+
+    assert_eq!(live.find_entity(|i, u| u.prototype_id[i] == 0), Some(0));
+    assert_eq!(live.find_entity(|i, u| u.hp[i] > 100), None);
+    assert_eq!(live.find_entities(|i, u| u.prototype_id[i] == 0), [0, 3]);
+    assert_eq!(live.find_entities(|i, u| u.name.has_override(i)), [3]);
+    assert!(live.any_entity(|i, u| u.hp[i] == 30));
+    assert!(!live.any_entity(|i, u| u.hp[i] == 31));
+}
+
+#[test]
+fn entity_comparisons() {
+    let prototypes = prototypes();
+    let mut live = live(&prototypes);
+
+    assert!(!live.entity_equals(0, 3));
+    assert_eq!(live.diff_entities(0, 3), ["name"]);
+    assert_eq!(live.diff_entities(0, 1), ["hp", "name"]);
+    assert!(live.diff_entities(0, 0).is_empty());
+
+    assert!(live.entities_equal_to_prototype(0, &prototypes, 0));
+    assert!(!live.entities_equal_to_prototype(3, &prototypes, 0));
+    assert_eq!(live.diff_entity_from_prototype(3, &prototypes, 0), ["name"]);
+    assert_eq!(live.diff_entity_from_prototype(1, &prototypes, 2), ["hp", "name"]);
+
+    // An override equal to the prototype value doesn't count as a difference.
+    live.name.set(3, "grunt".into());
+    assert!(live.entity_equals(0, 3));
+    assert!(live.entities_equal_to_prototype(3, &prototypes, 0));
+}
+
+#[test]
+fn swap_field_trades_one_field() {
+    let prototypes = prototypes();
+    let mut live = live(&prototypes);
+
+    live.swap_field(1, 3, "name").unwrap();
+    assert_eq!(names(&live), ["grunt", "Bob", "mage", "archer"]);
+    assert_eq!(live.hp, [10, 20, 30, 10]);
+
+    live.swap_field(0, 2, "hp").unwrap();
+    assert_eq!(live.hp, [30, 20, 10, 10]);
+
+    assert_eq!(live.swap_field(0, 1, "mana"), Err(UnknownFieldError { field: "mana".into() }));
+}
+
+#[test]
+fn swap_field_only_creates_overrides_it_needs() {
+    let prototypes = prototypes();
+    let mut live = live(&prototypes);
+
+    // Same prototype: entity 3's override just moves to entity 0.
+    live.swap_field(0, 3, "name").unwrap();
+    assert_eq!(names(&live), ["Bob", "archer", "mage", "grunt"]);
+    assert!(live.name.has_override(0));
+    assert!(!live.name.has_override(3));
+    assert_eq!(live.total_override_count(), 1);
+
+    // Different prototypes: each entity now needs its own value.
+    live.swap_field(1, 2, "name").unwrap();
+    assert_eq!(names(&live), ["Bob", "mage", "archer", "grunt"]);
+    assert_eq!(live.total_override_count(), 3);
+
+    // Both overridden: the values trade places, no new overrides.
+    live.swap_field(0, 1, "name").unwrap();
+    assert_eq!(names(&live), ["mage", "Bob", "archer", "grunt"]);
+    assert_eq!(live.total_override_count(), 3);
+}
+
+#[test]
+fn override_counts_and_reset() {
+    let prototypes = prototypes();
+    let mut live = live(&prototypes);
+    live.name.set(0, "Al".into());
+    live.name.set(1, "Cy".into());
+
+    assert_eq!(live.total_override_count(), 3);
+    assert_eq!(live.override_count_for_field("name"), 3);
+    assert_eq!(live.override_count_for_field("hp"), 0);
+
+    assert_eq!(live.reset_all_entities_of_prototype(0), 2);
+    assert_eq!(live.total_override_count(), 1);
+    assert_eq!(names(&live), ["grunt", "Cy", "mage", "grunt"]);
+}
+
+#[test]
+fn unknown_field_names_count_nothing() {
+    let live = live(&prototypes());
+    assert_eq!(live.override_count_for_field("mana"), 0);
+    assert_eq!(live.count_entities_with("mana", |_| true), 0);
+    assert!(live.map_field_values("mana", |v| v.clone()).is_empty());
+}
+
+#[test]
+fn entity_memory_size_counts_overrides() {
+    let prototypes = prototypes();
+    let live = live(&prototypes);
+    let hp = std::mem::size_of::<u32>();
+    assert_eq!(live.entity_memory_size(0), hp);
+    assert_eq!(live.entity_memory_size(3), hp + std::mem::size_of::<String>());
+}
+
+#[test]
+fn prototype_id_checks() {
+    let prototypes = prototypes();
+    let mut live = live(&prototypes);
+
+    assert_eq!(live.validate_prototype_ids(&prototypes), Ok(()));
+    assert!(prototypes.prototype_ids_are_unique());
+    assert!(!live.prototype_ids_are_unique());
+
+    live.prototype_id[1] = 7;
+    live.prototype_id[2] = 3;
+    assert_eq!(live.validate_prototype_ids(&prototypes), Err(vec![(1, 7), (2, 3)]));
+}
+
+#[test]
+fn binary_search_by_key_follows_slice_contract() {
+    let prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+    for proto in [0, 0, 1, 2] {
+        live.spawn_entity(&prototypes, proto);
+    }
+
+    assert_eq!(live.binary_search_by_key(&20, |i| live.hp[i]), Ok(2));
+    assert_eq!(live.binary_search_by_key(&30, |i| live.hp[i]), Ok(3));
+    assert_eq!(live.binary_search_by_key(&15, |i| live.hp[i]), Err(2));
+    assert_eq!(live.binary_search_by_key(&99, |i| live.hp[i]), Err(4));
+    assert_eq!(live.binary_search_by_key(&1, |i| live.hp[i]), Err(0));
+}
+
+#[test]
+fn length_and_index_helpers() {
+    let prototypes = prototypes();
+    let live = live(&prototypes);
+    let empty = Units::new_from_prototypes(&prototypes);
+
+    assert_eq!(live.len(), 4);
+    assert!(!live.is_empty());
+    assert!(empty.is_empty());
+    assert_eq!(prototypes.prototype_count(), 3);
+    assert_eq!(live.index_iter(), 0..4);
+    assert_eq!(live.iter_entity_chunks(3).collect::<Vec<_>>(), [0..3, 3..4]);
+    assert_eq!(live.iter_entity_chunks(4).next(), Some(0..4));
+    assert_eq!(live.iter_entity_chunks(4).count(), 1);
+    assert_eq!(empty.iter_entity_chunks(2).count(), 0);
+}
+
+#[test]
+fn systems_visit_every_entity() {
+    let prototypes = prototypes();
+    let mut live = live(&prototypes);
+
+    let bonus = [1, 2, 3, 4];
+    assert_eq!(live.zip_with(&bonus, |i, u, b| u.hp[i] + b[i]), [11, 22, 33, 14]);
+
+    live.apply_system(|u, i| u.hp[i] += 1);
+    assert_eq!(live.hp, [11, 21, 31, 11]);
+
+    live.filter_system(|u, i| u.prototype_id[i] == 0, |u, i| u.hp[i] = 0);
+    assert_eq!(live.hp, [0, 21, 31, 0]);
+}
+
+#[test]
+fn field_queries() {
+    let prototypes = prototypes();
+    let live = live(&prototypes);
+
+    assert_eq!(names(&live), ["grunt", "archer", "mage", "Bob"]);
+    assert_eq!(live.count_entities_with("hp", |v| v.as_u64() == Some(10)), 2);
+    assert_eq!(live.count_entities_with("name", |v| v == "Bob"), 1);
+    assert_eq!(live.count_entities_where(|u| &u.hp, |hp| *hp >= 20), 2);
+    assert_eq!(live.count_entities_where(|u| &u.name, |n: &String| n.len() == 5), 1);
+
+    assert_eq!(live.instance_count_matching("hp", &json!(10)), 2);
+    assert_eq!(live.instance_count_matching("name", &json!("grunt")), 1);
+    assert_eq!(live.instance_count_matching("name", &json!(5)), 0);
+    assert_eq!(live.instance_count_matching("mana", &json!(5)), 0);
+
+    assert_eq!(
+        live.map_field_values("name", |v| json!(v.as_str().unwrap().len())),
+        [json!(5), json!(6), json!(4), json!(3)]
+    );
+    assert_eq!(live.map_field_values("hp", |v| v.clone()), [json!(10), json!(20), json!(30), json!(10)]);
+}
@@ -0,0 +1,226 @@
+use grug_soa::{Overlay, UnknownFieldError};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Stats {
+    speed: f32,
+    armor: u32,
+}
+
+#[derive(Default)]
+struct Units {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    stats: Vec<Stats>,
+    name: Overlay<String>,
+}
+
+grug_soa::impl_load_prototype!(Units { prototype_id: usize, hp: u32, stats: Stats, name: String });
+
+fn prototypes() -> Units {
+    let mut prototypes = Units::default();
+    prototypes.load_prototype(json!({ "hp": 10, "stats": { "speed": 1.5, "armor": 3 }, "name": "grunt" }));
+    prototypes.load_prototype(json!({ "hp": 20, "stats": { "speed": 2.0, "armor": 1 }, "name": "archer" }));
+    prototypes
+}
+
+#[test]
+fn export_prototype_to_json_matches_the_loaded_object() {
+    let prototypes = prototypes();
+    assert_eq!(
+        prototypes.export_prototype_to_json(1),
+        json!({ "hp": 20, "stats": { "speed": 2.0, "armor": 1 }, "name": "archer" })
+    );
+}
+
+#[test]
+fn to_json_array_round_trips_through_load_prototype_batch() {
+    let prototypes = prototypes();
+    let exported = prototypes.to_json_array();
+    assert_eq!(exported.as_array().unwrap().len(), 2);
+    assert_eq!(exported[0]["name"], "grunt");
+
+    let mut reloaded = Units::default();
+    reloaded.load_prototype_batch(exported.clone());
+    assert_eq!(reloaded.prototype_id, [0, 1]);
+    assert_eq!(reloaded.hp, prototypes.hp);
+    assert_eq!(reloaded.stats, prototypes.stats);
+    assert_eq!(reloaded.to_json_array(), exported);
+}
+
+#[test]
+fn entity_to_json_loads_as_a_prototype() {
+    let prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 1);
+    live.name.set(0, "Robin".into());
+    live.hp[0] = 7;
+
+    let json = live.entity_to_json(0);
+    assert_eq!(json, json!({ "hp": 7, "stats": { "speed": 2.0, "armor": 1 }, "name": "Robin" }));
+
+    let mut clipboard = prototypes;
+    clipboard.load_prototype(json);
+    assert_eq!(clipboard.name.dense_data[2], "Robin");
+    assert_eq!(clipboard.hp[2], 7);
+}
+
+#[test]
+fn snapshot_and_restore_entities() {
+    let prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+    for proto in [0, 1, 0] {
+        live.spawn_entity(&prototypes, proto);
+    }
+    live.name.set(2, "Hero".into());
+
+    let snapshot = live.snapshot_entities(&[2, 1]);
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot[0]["index"], 2);
+    assert_eq!(snapshot[0]["prototype_id"], 0);
+    assert_eq!(snapshot[0]["fields"]["name"], "Hero");
+    assert_eq!(snapshot[0]["fields"]["hp"], 10);
+    // overlay fields without an override are left out
+    assert!(snapshot[1]["fields"].get("name").is_none());
+
+    live.hp[2] = 1;
+    live.name.set(2, "Zero".into());
+    live.name.set(1, "Temp".into());
+    live.restore_entities(snapshot);
+
+    assert_eq!(live.hp[2], 10);
+    assert_eq!(live.name.get(2, 0), "Hero");
+    assert!(!live.name.has_override(1));
+    assert_eq!(live.name.get(1, 1), "archer");
+}
+
+#[test]
+fn component_default_json_lists_every_field() {
+    assert_eq!(
+        Units::component_default_json(),
+        json!({ "hp": 0, "stats": { "speed": 0.0, "armor": 0 }, "name": "" })
+    );
+}
+
+#[test]
+fn load_prototype_from_env_reads_prefixed_variables() {
+    // SAFETY: the variables are unique to this test, and no other test reads the environment.
+    unsafe {
+        std::env::set_var("SOA_EXPORT_TEST_HP", "42");
+        std::env::set_var("SOA_EXPORT_TEST_NAME", "goblin");
+        std::env::set_var("SOA_EXPORT_TEST_STATS", r#"{ "speed": 0.5, "armor": 9 }"#);
+    }
+    let mut prototypes = prototypes();
+    let id = prototypes.load_prototype_from_env("SOA_EXPORT_TEST");
+
+    assert_eq!(id, 2);
+    assert_eq!(prototypes.hp[2], 42);
+    assert_eq!(prototypes.name.dense_data[2], "goblin");
+    assert_eq!(prototypes.stats[2], Stats { speed: 0.5, armor: 9 });
+
+    // Missing variables use the field defaults.
+    let id = prototypes.load_prototype_from_env("SOA_EXPORT_MISSING");
+    assert_eq!(prototypes.hp[id], 0);
+    assert_eq!(prototypes.name.dense_data[id], "");
+}
+
+#[test]
+fn prototype_as_snapshot_clones_every_field() {
+    let prototypes = prototypes();
+    let (hp, stats, name) = prototypes.prototype_as_snapshot(0);
+    assert_eq!(hp, 10);
+    assert_eq!(stats, Stats { speed: 1.5, armor: 3 });
+    assert_eq!(name, "grunt");
+}
+
+#[test]
+fn clone_prototype_appends_a_copy() {
+    let mut prototypes = prototypes();
+    let elite = prototypes.clone_prototype(1);
+    assert_eq!(elite, 2);
+    assert_eq!(prototypes.prototype_id, [0, 1, 2]);
+    assert_eq!(prototypes.export_prototype_to_json(2), prototypes.export_prototype_to_json(1));
+
+    prototypes.hp[elite] = 200;
+    assert_eq!(prototypes.hp, [10, 20, 200]);
+}
+
+#[test]
+fn load_prototypes_from_slices_skips_json() {
+    let mut prototypes = prototypes();
+    prototypes.load_prototypes_from_slices(
+        &[5, 6],
+        &[Stats::default(), Stats { speed: 3.0, armor: 2 }],
+        &["imp".into(), "bat".into()],
+    );
+    assert_eq!(prototypes.prototype_id, [0, 1, 2, 3]);
+    assert_eq!(prototypes.hp, [10, 20, 5, 6]);
+    assert_eq!(prototypes.stats[3].armor, 2);
+    assert_eq!(prototypes.name.dense_data, ["grunt", "archer", "imp", "bat"]);
+}
+
+#[test]
+#[should_panic(expected = "slice lengths differ")]
+fn load_prototypes_from_slices_rejects_ragged_slices() {
+    prototypes().load_prototypes_from_slices(&[5], &[], &["imp".into()]);
+}
+
+#[test]
+fn scale_prototype_field_and_copies() {
+    let mut prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 0);
+    live.spawn_entity(&prototypes, 1);
+
+    prototypes.scale_prototype_field("hp", 1.25).unwrap();
+    prototypes.scale_prototype_field("stats", 2.0).unwrap();
+    assert_eq!(prototypes.hp, [13, 25]);
+    assert_eq!(prototypes.stats[0], Stats { speed: 3.0, armor: 6 });
+
+    // Dense fields of spawned entities keep their values; non-numeric fields are untouched.
+    live.scale_prototype_field_copies("hp", 1.25).unwrap();
+    live.scale_prototype_field_copies("name", 2.0).unwrap();
+    assert_eq!(live.hp, [10, 20]);
+    assert_eq!(live.name.get(1, 1), "archer");
+}
+
+#[test]
+fn scale_unknown_field_is_an_error() {
+    let mut prototypes = prototypes();
+    let mana = Err(UnknownFieldError { field: "mana".into() });
+    assert_eq!(prototypes.scale_prototype_field("mana", 2.0), mana);
+    assert_eq!(prototypes.scale_prototype_field_copies("mana", 2.0), mana);
+    assert_eq!(prototypes.hp, [10, 20]);
+}
+
+#[test]
+fn to_tabular_string_marks_overrides() {
+    let prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 0);
+    live.spawn_entity(&prototypes, 1);
+    live.name.set(1, "Robin".into());
+
+    let table = live.to_tabular_string();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("prototype_id | hp | stats"));
+    assert!(lines[0].ends_with("| name"));
+    assert!(lines[1].starts_with("0            | 10 | Stats { speed: 1.5, armor: 3 }"));
+    assert!(lines[1].ends_with("| \"grunt\""));
+    assert!(lines[2].ends_with("| \"Robin\"*"));
+
+    // Same data, printed line by line.
+    live.debug_print_entity(1);
+}
+
+#[cfg(feature = "json_schema")]
+#[test]
+fn json_schema_describes_every_field() {
+    let schema = Units::json_schema();
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema["properties"]["hp"]["type"], "integer");
+    assert_eq!(schema["properties"]["name"]["type"], "string");
+    assert_eq!(schema["properties"]["stats"]["type"], "object");
+}
@@ -0,0 +1,90 @@
+use grug_soa::{Overlay, SmallOverlay};
+use serde_json::json;
+
+#[derive(Default)]
+struct Units {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    pos: Vec<(f64, f64)>,
+    name: Overlay<String>,
+    tag: SmallOverlay<u8>,
+}
+
+grug_soa::impl_load_prototype!(Units { prototype_id: usize, hp: u32, pos: (f64, f64), name: String, tag: u8 });
+
+fn prototypes() -> Units {
+    let mut prototypes = Units::default();
+    prototypes.load_prototype(json!({ "hp": 10, "name": "grunt" }));
+    prototypes.load_prototype(json!({ "hp": 20, "name": "archer" }));
+    prototypes
+}
+
+#[test]
+fn names_and_types() {
+    assert_eq!(Units::component_names(), ["hp", "pos", "name", "tag"]);
+    assert_eq!(
+        Units::field_names_and_types(),
+        [("hp", "u32"), ("pos", "(f64, f64)"), ("name", "String"), ("tag", "u8")]
+    );
+}
+
+#[test]
+fn field_sizes() {
+    assert_eq!(Units::field_size_of("hp"), 4);
+    assert_eq!(Units::field_size_of("pos"), 16);
+    assert_eq!(Units::field_size_of("name"), std::mem::size_of::<String>());
+    assert_eq!(Units::field_size_of("tag"), 1);
+    assert_eq!(Units::field_size_of("mana"), 0);
+
+    let row = 4 + 16 + std::mem::size_of::<String>() + 1;
+    assert_eq!(Units::total_prototype_memory(0), 0);
+    assert_eq!(Units::total_prototype_memory(3), 3 * row);
+}
+
+#[test]
+fn overlay_fields_are_reported() {
+    assert!(!Units::field_type_is_overlay("hp"));
+    assert!(!Units::field_type_is_overlay("pos"));
+    assert!(Units::field_type_is_overlay("name"));
+    assert!(Units::field_type_is_overlay("tag"));
+    assert!(!Units::field_type_is_overlay("mana"));
+}
+
+#[test]
+fn capacity_summary_reports_every_field() {
+    let prototypes = prototypes();
+    let mut live = Units::new_from_prototypes(&prototypes);
+    live.hp.reserve_exact(8);
+    for proto in [0, 1, 1] {
+        live.spawn_entity(&prototypes, proto);
+    }
+
+    let summary = live.capacity_summary();
+    let names: Vec<&str> = summary.iter().map(|&(name, _, _)| name).collect();
+    assert_eq!(names, ["prototype_id", "hp", "pos", "name", "tag"]);
+    assert!(summary.iter().all(|&(_, _, len)| len == 3));
+    assert!(summary.iter().all(|&(_, capacity, _)| capacity >= 2));
+    assert!(summary[1].1 >= 8);
+}
+
+#[test]
+fn length_checks_on_both_kinds_of_table() {
+    let mut prototypes = prototypes();
+    assert!(prototypes.len_consistent());
+    assert!(prototypes.field_lens_equal());
+
+    let mut live = Units::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 1);
+    assert!(live.len_consistent());
+    assert!(live.field_lens_equal());
+
+    // A dense field out of step with the rest.
+    live.hp.push(1);
+    assert!(!live.len_consistent());
+    assert!(!live.field_lens_equal());
+
+    // prototype_id out of step, fields still equal.
+    prototypes.prototype_id.push(2);
+    assert!(!prototypes.len_consistent());
+    assert!(prototypes.field_lens_equal());
+}
@@ -0,0 +1,35 @@
+use grug_soa::SortedVec;
+
+#[test]
+fn insert_keeps_values_sorted() {
+    let mut events = SortedVec::default();
+    assert_eq!(events.insert(30), 0);
+    assert_eq!(events.insert(10), 0);
+    assert_eq!(events.insert(20), 1);
+    assert_eq!(events.insert(40), 3);
+    assert_eq!(events.as_slice(), &[10, 20, 30, 40]);
+    assert_eq!(events.binary_search(&30), Ok(2));
+}
+
+#[test]
+fn insert_duplicates_next_to_each_other() {
+    let mut events = SortedVec::default();
+    for value in [5, 1, 5, 3, 5] {
+        events.insert(value);
+    }
+    assert_eq!(events.as_slice(), &[1, 3, 5, 5, 5]);
+}
+
+#[test]
+fn remove_is_stable() {
+    let mut events = SortedVec::default();
+    for value in [4, 2, 8, 6] {
+        events.insert(value);
+    }
+    assert_eq!(events.remove(1), 4);
+    assert_eq!(events.as_slice(), &[2, 6, 8]);
+    assert_eq!(events.remove(0), 2);
+    assert_eq!(events.remove(1), 8);
+    assert_eq!(events.as_slice(), &[6]);
+    assert_eq!(events.len(), 1);
+}
@@ -0,0 +1,129 @@
+use grug_soa::{Overlay, SparseOverlay};
+
+const PROTO: u32 = 1000;
+
+fn check_matches(sparse: &SparseOverlay<u32>, overlay: &Overlay<u32>, context: &str) {
+    assert_eq!(sparse.instances_len(), overlay.instances_len(), "{context}");
+    for i in 0..overlay.instances_len() {
+        assert_eq!(sparse.has_override(i), overlay.has_override(i), "{context} instance {i}");
+        assert_eq!(sparse.get(i, 0), overlay.get(i, 0), "{context} instance {i}");
+    }
+}
+
+fn pair(n: usize) -> (SparseOverlay<u32>, Overlay<u32>) {
+    let mut sparse = SparseOverlay::default();
+    let mut overlay = Overlay::default();
+    sparse.dense_data.push(PROTO);
+    overlay.dense_data.push(PROTO);
+    for _ in 0..n {
+        sparse.push_instance();
+        overlay.push_instance();
+    }
+    (sparse, overlay)
+}
+
+#[test]
+fn runs_split_and_merge_across_words() {
+    // Overrides in words 0, 2 and 4 make three runs; filling words 1 and 3 merges them.
+    let (mut sparse, mut overlay) = pair(64 * 5);
+    for i in [0, 64 * 2, 64 * 4] {
+        sparse.set(i, i as u32);
+        overlay.set(i, i as u32);
+        check_matches(&sparse, &overlay, "split");
+    }
+    for i in [64, 64 * 3] {
+        sparse.set(i + 63, i as u32);
+        overlay.set(i + 63, i as u32);
+        check_matches(&sparse, &overlay, "merge");
+    }
+
+    // Clearing a middle word splits the run again; clearing the rest leaves no runs.
+    for i in [64 * 2, 0, 64 * 4, 64 + 63, 64 * 3 + 63] {
+        sparse.clear_override(i);
+        overlay.clear_override(i);
+        check_matches(&sparse, &overlay, "clear");
+    }
+    assert!((0..64 * 5).all(|i| !sparse.has_override(i)));
+}
+
+#[test]
+fn from_overlay_keeps_overrides() {
+    let (_, mut overlay) = pair(200);
+    for i in (0..200).filter(|i| i % 7 == 0 || (64..128).contains(i)) {
+        overlay.set(i, i as u32);
+    }
+    let sparse = SparseOverlay::from_overlay(&overlay);
+    assert_eq!(sparse.prototypes_len(), 1);
+    check_matches(&sparse, &overlay, "from_overlay");
+}
+
+#[test]
+fn swap_remove_across_word_boundary() {
+    for (n, index) in [(65, 63), (65, 0), (129, 64), (130, 1)] {
+        let (mut sparse, mut overlay) = pair(n);
+        for i in [index, n - 1] {
+            sparse.set(i, i as u32);
+            overlay.set(i, i as u32);
+        }
+        sparse.swap_remove_instance(index);
+        overlay.swap_remove_instance(index);
+        check_matches(&sparse, &overlay, &format!("n={n} index={index}"));
+    }
+}
+
+#[test]
+fn randomized_against_overlay() {
+    let (mut sparse, mut overlay) = pair(0);
+
+    // xorshift, so failures reproduce.
+    let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+    let mut rand = |n: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % n as u64) as usize
+    };
+
+    for step in 0..5_000 {
+        let len = overlay.instances_len();
+        match rand(6) {
+            _ if len == 0 => {
+                sparse.push_instance();
+                overlay.push_instance();
+            }
+            0 | 1 => {
+                // Pushes outnumber removals so the overlay spans several words.
+                sparse.push_instance();
+                overlay.push_instance();
+            }
+            2 => {
+                let i = rand(len);
+                let v = rand(1000) as u32;
+                sparse.set(i, v);
+                overlay.set(i, v);
+            }
+            3 => {
+                let i = rand(len);
+                sparse.clear_override(i);
+                overlay.clear_override(i);
+            }
+            4 => {
+                let i = rand(len);
+                sparse.swap_remove_instance(i);
+                overlay.swap_remove_instance(i);
+            }
+            _ => {
+                let i = rand(len);
+                *sparse.get_mut(i, 0) += 1;
+                *overlay.get_mut(i, 0) += 1;
+            }
+        }
+
+        overlay.check_invariants();
+        check_matches(&sparse, &overlay, &format!("step {step}"));
+        if step % 1000 == 0 {
+            check_matches(&SparseOverlay::from_overlay(&overlay), &overlay, &format!("from_overlay step {step}"));
+        }
+    }
+    assert!(overlay.instances_len() > 128, "walk should span several words");
+}
@@ -0,0 +1,152 @@
+use grug_soa::{Overlay, OverlayI64, OverlayNoCopy, OverlayShared, SmallOverlay, SparseOverlay, Storage};
+use serde_json::json;
+
+#[derive(Default)]
+struct Mobs {
+    prototype_id: Vec<usize>,
+    hp: Vec<u32>,
+    plain: Overlay<String>,
+    no_copy: OverlayNoCopy<String>,
+    shared: OverlayShared<String>,
+    sparse: SparseOverlay<String>,
+    small: SmallOverlay<String>,
+    keyed: OverlayI64<String>,
+}
+
+grug_soa::impl_load_prototype!(Mobs {
+    prototype_id: usize,
+    hp: u32,
+    plain: String,
+    no_copy: String,
+    shared: String,
+    sparse: String,
+    small: String,
+    keyed: String,
+});
+
+const NAMES: [&str; 3] = ["goblin", "orc", "elf"];
+
+fn prototypes() -> Mobs {
+    let mut prototypes = Mobs::default();
+    for (i, name) in NAMES.iter().enumerate() {
+        prototypes.load_prototype(json!({
+            "hp": 5 * 10u32.pow(i as u32),
+            "plain": name,
+            "no_copy": name,
+            "shared": name,
+            "sparse": name,
+            "small": name,
+            "keyed": name,
+        }));
+    }
+    prototypes
+}
+
+/// Effective value of entity `i` in every overlay field, through the `Storage` trait.
+fn values(mobs: &Mobs, i: usize) -> [String; 6] {
+    let proto = mobs.prototype_id[i];
+    [
+        mobs.plain.get_entity(i, proto).clone(),
+        mobs.no_copy.get_entity(i, proto).clone(),
+        mobs.shared.get_entity(i, proto).clone(),
+        mobs.sparse.get_entity(i, proto).clone(),
+        mobs.small.get_entity(i, proto).clone(),
+        mobs.keyed.get_entity(i, proto).clone(),
+    ]
+}
+
+/// Prototype row `proto` of every overlay field.
+fn prototype_row(mobs: &Mobs, proto: usize) -> [String; 6] {
+    [
+        mobs.plain.prototype(proto).clone(),
+        mobs.no_copy.prototype(proto).clone(),
+        mobs.shared.prototype(proto).clone(),
+        mobs.sparse.prototype(proto).clone(),
+        mobs.small.prototype(proto).clone(),
+        mobs.keyed.prototype(proto).clone(),
+    ]
+}
+
+fn all(value: &str) -> [String; 6] {
+    std::array::from_fn(|_| value.to_string())
+}
+
+fn live_table(prototypes: &Mobs) -> Mobs {
+    let mut live = Mobs::new_from_prototypes(prototypes);
+    for proto in [0, 1, 2, 0] {
+        live.spawn_entity(prototypes, proto);
+    }
+    live.plain.set(3, "custom".into());
+    live.no_copy.set(3, Box::new("custom".into()));
+    live.shared.set(3, "custom".into());
+    live.sparse.set(3, "custom".into());
+    live.small.set(3, "custom".into());
+    live.keyed.set(3, "custom".into());
+    live
+}
+
+#[test]
+fn swap_reorders_every_prototype_field() {
+    let mut prototypes = prototypes();
+    prototypes.swap_prototypes(0, 2);
+
+    assert_eq!(prototypes.prototype_id, [0, 1, 2]);
+    assert_eq!(prototypes.hp, [500, 50, 5]);
+    assert_eq!(prototype_row(&prototypes, 0), all("elf"));
+    assert_eq!(prototype_row(&prototypes, 1), all("orc"));
+    assert_eq!(prototype_row(&prototypes, 2), all("goblin"));
+}
+
+#[test]
+fn remap_keeps_live_entities_on_their_data() {
+    let mut prototypes = prototypes();
+    let mut live = live_table(&prototypes);
+
+    prototypes.swap_prototypes(0, 2);
+    live.remap_swapped_prototypes(0, 2);
+
+    assert_eq!(live.prototype_id, [2, 1, 0, 2]);
+    assert_eq!(live.hp, [5, 50, 500, 5]);
+    assert_eq!(values(&live, 0), all("goblin"));
+    assert_eq!(values(&live, 1), all("orc"));
+    assert_eq!(values(&live, 2), all("elf"));
+    assert_eq!(values(&live, 3), all("custom"));
+
+    // Clearing the override falls back to the (moved) goblin prototype.
+    live.plain.clear_override(3);
+    assert_eq!(live.plain.get(3, live.prototype_id[3]), "goblin");
+
+    // New spawns use the new order.
+    live.spawn_entity(&prototypes, 0);
+    assert_eq!(values(&live, 4), all("elf"));
+    assert_eq!(live.hp[4], 500);
+}
+
+#[test]
+fn swap_back_restores_the_original_order() {
+    let mut prototypes = prototypes();
+    let mut live = live_table(&prototypes);
+
+    for _ in 0..2 {
+        prototypes.swap_prototypes(1, 2);
+        live.remap_swapped_prototypes(1, 2);
+    }
+
+    assert_eq!(live.prototype_id, [0, 1, 2, 0]);
+    for (proto, name) in NAMES.iter().enumerate() {
+        assert_eq!(prototype_row(&prototypes, proto), all(name));
+        assert_eq!(values(&live, proto), all(name));
+    }
+}
+
+#[test]
+fn swap_with_itself_is_a_no_op() {
+    let mut prototypes = prototypes();
+    let mut live = live_table(&prototypes);
+    prototypes.swap_prototypes(1, 1);
+    live.remap_swapped_prototypes(1, 1);
+
+    assert_eq!(live.prototype_id, [0, 1, 2, 0]);
+    assert_eq!(values(&live, 1), all("orc"));
+    assert_eq!(prototype_row(&prototypes, 1), all("orc"));
+}
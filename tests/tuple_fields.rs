@@ -0,0 +1,27 @@
+use grug_soa::Overlay;
+use serde_json::json;
+
+#[derive(Default)]
+struct Shapes {
+    prototype_id: Vec<usize>,
+    position: Vec<(f32, f32)>,
+    color: Overlay<(u8, u8, u8)>,
+}
+
+grug_soa::impl_load_prototype!(Shapes { prototype_id: usize, position: (f32, f32), color: (u8, u8, u8) });
+
+#[test]
+fn tuple_fields_load_from_json_arrays() {
+    let mut prototypes = Shapes::default();
+    prototypes.load_prototype(json!({ "position": [1.5, -2.0], "color": [255, 128, 0] }));
+    prototypes.load_prototype(json!({}));
+
+    assert_eq!(prototypes.position, [(1.5, -2.0), (0.0, 0.0)]);
+    assert_eq!(prototypes.color.dense_data, [(255, 128, 0), (0, 0, 0)]);
+
+    let mut live = Shapes::new_from_prototypes(&prototypes);
+    live.spawn_entity(&prototypes, 0);
+    assert_eq!(live.position[0], (1.5, -2.0));
+    assert_eq!(*live.color.get(0, live.prototype_id[0]), (255, 128, 0));
+    assert_eq!(live.entity_to_json(0)["color"], json!([255, 128, 0]));
+}
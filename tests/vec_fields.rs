@@ -0,0 +1,29 @@
+use serde_json::json;
+
+#[derive(Default)]
+struct Inventories {
+    prototype_id: Vec<usize>,
+    items: Vec<Vec<u32>>,
+}
+
+grug_soa::impl_load_prototype!(Inventories { prototype_id: usize, items: Vec<u32> });
+
+#[test]
+fn variable_length_fields_load_and_swap_remove() {
+    let mut prototypes = Inventories::default();
+    prototypes.load_prototype_batch(json!([
+        { "items": [1, 2, 3] },
+        { "items": [] },
+        { "items": [4] },
+    ]));
+    assert_eq!(prototypes.items, [vec![1, 2, 3], vec![], vec![4]]);
+
+    let mut live = Inventories::new_from_prototypes(&prototypes);
+    for proto in 0..3 {
+        live.spawn_entity(&prototypes, proto);
+    }
+    live.items[0].push(9);
+    live.swap_remove(1);
+    assert_eq!(live.items, [vec![1, 2, 3, 9], vec![4]]);
+    assert_eq!(live.entity_to_json(1)["items"], json!([4]));
+}